@@ -1,6 +1,26 @@
+// The crate is still a single binary, so most of the API is only exercised from `main`.
+// Methods taking a file path are left out of wasm builds; use the `*_bytes` variants there.
+#![allow(dead_code)]
+
 // Import necessary modules
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{ HashMap, HashSet };
 use std::fmt;
-use calamine::{ Reader, open_workbook, Xlsx, DataType, Data, ExcelDateTime };
+#[cfg(not(target_family = "wasm"))]
+use std::fs::File;
+use std::hash::{ Hash, Hasher };
+use std::sync::Arc;
+use std::io::{ Cursor, Read, Seek };
+#[cfg(not(target_family = "wasm"))]
+use std::io::Write;
+use calamine::{ Reader, Ods, Xls, Xlsx, DataType, Data, Dimensions, Range };
+#[cfg(not(target_family = "wasm"))]
+use calamine::open_workbook;
+#[cfg(not(target_family = "wasm"))]
+use flate2::read::GzDecoder;
+#[cfg(not(target_family = "wasm"))]
+use zip::write::{ SimpleFileOptions, ZipWriter };
 
 // Enums
 
@@ -15,16 +35,115 @@ enum DateParseError {
     InvalidDate, // Added for date conversion errors
 }
 
-/// Enum for different types a cell can have.
+/// One piece of a date format string such as `"DD.MM.YYYY"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateToken {
+    Year,
+    ShortYear,
+    Month,
+    Day,
+    Literal(char),
+}
+
+/// Custom error type for DataFrame operations.
 #[derive(Debug)]
+enum DataFrameError {
+    ColumnNotFound(String),
+    DuplicateColumn(String),
+    MissingHeaders,
+    Io(std::io::Error),
+    Calamine(calamine::Error),
+    DateParse(DateParseError),
+    EmptySheet(String),
+    InvalidSheetName(String),
+    ShapeMismatch((usize, usize), (usize, usize)),
+    ColumnOutOfRange(usize),
+    LengthMismatch { expected: usize, found: usize },
+    RowOutOfRange(usize),
+    HeaderWidthMismatch { headers: usize, data: usize },
+    NoRows,
+    NoNumericValues(usize),
+    UnsupportedFormat(String),
+    Parquet(String),
+    NonNumeric { col: usize, row: usize },
+    NullValue { col: usize, row: usize },
+    UnsupportedEncoding(String),
+    UnsortedBins,
+}
+
+/// Crate-wide result type; the error defaults to `DataFrameError`.
+type Result<T, E = DataFrameError> = std::result::Result<T, E>;
+
+/// One disagreement found by `DataFrame::diff`: `(row, col, ours, theirs)`.
+type CellDiff<'a> = (usize, usize, Option<&'a CellValues>, Option<&'a CellValues>);
+
+/// Enum for the type of a column, used to force types on read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DType {
+    Int,
+    Float,
+    Text,
+    Date,
+    #[cfg(feature = "decimal")]
+    Decimal,
+    Bool,
+}
+
+/// How a workbook counts date serials. Workbooks made by old Mac versions of
+/// Excel use 1904, where serial 0 is 1904-01-01 instead of 1900-01-00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum DateSystem {
+    #[default]
+    Excel1900,
+    Excel1904,
+}
+
+/// What numeric methods do with a non-null cell that isn't a number, like
+/// stray text in an amount column. See `DataFrame::column_as_f64_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum CoercionPolicy {
+    /// Treat the cell as a null: leave it out of aggregates and give `None`
+    /// in per-row results, as the plain methods (`mean`, `column_as_f64`, ...) do.
+    #[default]
+    Skip,
+    /// Fail with `DataFrameError::NonNumeric`.
+    Error,
+}
+
+/// One problem found by `DataFrame::validate_schema`.
+#[derive(Debug, Clone, PartialEq)]
+enum SchemaMismatch {
+    MissingColumn(String),
+    ExtraColumn(String),
+    TypeMismatch { column: String, expected: DType, found: DType },
+}
+
+/// Enum for different types a cell can have.
+#[derive(Debug, Clone)]
 enum CellValues {
     Int(i32),
+    /// Integers outside the `i32` range. Small integers stay in `Int` so the common
+    /// case doesn't pay for the wider type; `as_f64` and `DType::Int` treat both alike.
+    Int64(i64),
     Float(f64),
-    Text(String),
+    /// Shared so that `intern_column` can point repeated strings at one allocation.
+    Text(Arc<str>),
     Date(Date),
+    /// Exact fixed-point number, e.g. money; see `DataFrame::cast_column`.
+    #[cfg(feature = "decimal")]
+    Decimal(Decimal),
+    /// Only produced when `ReadOptions::bool_tokens` is set; Excel booleans are Text otherwise.
+    Bool(bool),
+    /// A formula error such as `#DIV/0!`, kept when `ReadOptions::keep_errors` is set.
+    Error(String),
 }
 
 //traits
+/// Builds a typed record from one row of a frame, see `DataFrame::iter_as`.
+trait FromRow: Sized {
+    fn from_row(row: &[Cell]) -> Result<Self, DataFrameError>;
+}
+
 // Implement the conversion for different types
 impl From<i32> for CellValues {
     fn from(value: i32) -> Self {
@@ -32,6 +151,16 @@ impl From<i32> for CellValues {
     }
 }
 
+// Picks `Int` when the value fits and `Int64` otherwise
+impl From<i64> for CellValues {
+    fn from(value: i64) -> Self {
+        match i32::try_from(value) {
+            Ok(small) => CellValues::Int(small),
+            Err(_) => CellValues::Int64(value),
+        }
+    }
+}
+
 impl From<f64> for CellValues {
     fn from(value: f64) -> Self {
         CellValues::Float(value)
@@ -40,228 +169,4533 @@ impl From<f64> for CellValues {
 
 impl From<String> for CellValues {
     fn from(value: String) -> Self {
-        CellValues::Text(value)
+        CellValues::Text(value.into())
     }
 }
 
 // Also allow &str to be converted to Text as owned
 impl From<&str> for CellValues {
     fn from(value: &str) -> Self {
-        CellValues::Text(value.to_string())
+        CellValues::Text(value.into())
+    }
+}
+/// Merged cell ranges of a worksheet, used by `ReadOptions::fill_merged`.
+/// Only xlsx exposes them; the other workbook types report none.
+trait MergedRegions {
+    fn merged_regions_of(&mut self, sheet: &str) -> Result<Vec<Dimensions>>;
+}
+
+impl<RS: Read + Seek> MergedRegions for Xlsx<RS> {
+    fn merged_regions_of(&mut self, sheet: &str) -> Result<Vec<Dimensions>> {
+        self.load_merged_regions()?;
+        Ok(self.merged_regions_by_sheet(sheet).into_iter().map(|(_, _, region)| *region).collect())
+    }
+}
+
+impl<RS: Read + Seek> MergedRegions for Xls<RS> {
+    fn merged_regions_of(&mut self, _sheet: &str) -> Result<Vec<Dimensions>> {
+        Ok(vec![])
+    }
+}
+
+impl<RS: Read + Seek> MergedRegions for Ods<RS> {
+    fn merged_regions_of(&mut self, _sheet: &str) -> Result<Vec<Dimensions>> {
+        Ok(vec![])
     }
 }
+
 // Structs
 
 ///Struct to hold CellValues
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Cell {
     value: Option<CellValues>,
 }
 
 /// Struct to represent a Date.
-#[derive(Debug)]
+/// Field order matters: the derived `Ord` compares year, then month, then day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct Date {
     year: u32,
     month: u8,
     day: u8,
 }
 
+/// Fixed-point number worth `mantissa / 10^scale`, e.g. 12.30 is
+/// `{ mantissa: 1230, scale: 2 }`. Sums and products are exact, unlike `f64`.
+/// Needs the `decimal` feature.
+/// Values that differ only in trailing zeros (12.3 and 12.30) compare and hash equal.
+#[cfg(feature = "decimal")]
+#[derive(Debug, Clone, Copy)]
+struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+/// Separators used to parse numbers stored as text, e.g. `1.234,56` in
+/// European locales is `decimal_separator: ','` with `thousands_separator: Some('.')`.
+#[derive(Debug, Clone, Copy)]
+struct NumberFormat {
+    decimal_separator: char,
+    thousands_separator: Option<char>,
+}
+
+/// Values read as booleans by `DataFrame::infer_bools`, compared trimmed and
+/// ignoring case. The default sets are `true`/`yes`/`y`/`1` and `false`/`no`/`n`/`0`.
+#[derive(Debug, Clone)]
+struct BoolTokens {
+    truthy: Vec<String>,
+    falsy: Vec<String>,
+}
+
+/// Options controlling how worksheet cells are turned into `CellValues`.
+#[derive(Debug, Clone)]
+struct ReadOptions {
+    /// Worksheet to read; `None` reads "Sheet1". Ignored for CSV.
+    sheet: Option<String>,
+    /// Whether the first row holds headers; `None` guesses via `detect_headers`.
+    with_headers: Option<bool>,
+    /// Field separator for CSV files.
+    delimiter: char,
+    /// Text encoding of CSV files by WHATWG label, e.g. `"windows-1252"`.
+    /// `None` requires valid UTF-8; other encodings need the `encoding_rs` feature.
+    encoding: Option<String>,
+    /// Date system used to turn serials into dates.
+    date_system: DateSystem,
+    /// Text values that are read as nulls, e.g. `"NA"` or `"-"`.
+    null_values: Vec<String>,
+    /// Columns forced to a type instead of keeping what the sheet stores,
+    /// e.g. `(0, DType::Text)` for ZIP codes. Cells that don't fit become nulls.
+    column_types: Vec<(usize, DType)>,
+    /// When set, text cells are parsed as numbers in this format where possible,
+    /// scientific notation like `"1.5e3"` included. Opt-in: `None` keeps every
+    /// workbook text cell as text, so IDs like `"00501"` aren't turned into numbers.
+    number_format: Option<NumberFormat>,
+    /// Drop fully blank rows and columns around the table, e.g. padding above
+    /// the real header row. Column indices in `column_types` count from the trimmed edge.
+    trim_empty: bool,
+    /// Keep formula errors as `CellValues::Error` instead of reading them as nulls.
+    keep_errors: bool,
+    /// Rename duplicate header names with `dedupe_headers` after reading.
+    dedupe_headers: bool,
+    /// Keep date cells as their raw Excel serial (`Float`) instead of a `Date`.
+    keep_date_serials: bool,
+    /// Copy the value of each merged region (xlsx only) into all of its cells
+    /// instead of leaving them empty. `ffill_column` does the same by column.
+    fill_merged: bool,
+    /// Stop after this many data rows, counted after the header row. This reads
+    /// the top of the sheet only, not a sample spread over it.
+    max_rows: Option<usize>,
+    /// Rows dropped from the top before anything else, e.g. a title banner.
+    /// The header row, if any, is the first row after them.
+    skip_rows: usize,
+    /// Only read these columns, in this order. Indices count sheet columns, same
+    /// as `column_types`; columns past the end of a row read as nulls.
+    usecols: Option<Vec<usize>>,
+    /// Turn columns where every value is one of these tokens into `CellValues::Bool`.
+    /// `None` (the default) leaves them alone, so `"1"` stays Int.
+    bool_tokens: Option<BoolTokens>,
+}
+
+/// What a read with options had to give up on, returned by `read` and the
+/// `*_with` readers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ReadReport {
+    /// `(column, type, cells)` for each `ReadOptions::column_type` that some
+    /// cells didn't fit; those cells were read as nulls.
+    coercion_failures: Vec<(usize, DType, usize)>,
+}
+
 /// Struct for DataFrame which uses the Cell enum.
 #[derive(Debug)]
 struct DataFrame {
+    headers: Option<Vec<String>>,
     data: Vec<Vec<Cell>>,
 }
 
+/// Read-only numeric view of a column, see `DataFrame::column_view_f64`.
+/// Cells are converted as they are visited instead of being copied out first.
+#[derive(Debug, Clone, Copy)]
+struct ColumnView<'a> {
+    frame: &'a DataFrame,
+    col: usize,
+}
+
+/// FNV-1a over explicit little-endian bytes, used by `DataFrame::content_hash`
+/// because std `Hash` impls may change between Rust releases and platforms.
+struct Fnv1aHasher(u64);
+
 // Implementations
-impl DataFrame {
-    fn handle_dates(data: &Data) -> Option<Date> {
-        match data {
-            Data::DateTime(excel_date_time) => {
-                // Check if value can be converted to f64 and then to u32
-                let serial_number = excel_date_time.as_f64() as u32;
-                let date = Date::from_excel_datetype(serial_number).ok();
-                println!("Date extracted: {:?}", date);
-                date
-            }
-            _ => {
-                println!("Not a DateTime: {:?}", data);
-                None
-            }
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            sheet: None,
+            with_headers: None,
+            delimiter: ',',
+            encoding: None,
+            date_system: DateSystem::Excel1900,
+            null_values: vec![String::new()],
+            column_types: vec![],
+            number_format: None,
+            trim_empty: false,
+            keep_errors: false,
+            dedupe_headers: false,
+            keep_date_serials: false,
+            fill_merged: false,
+            max_rows: None,
+            skip_rows: 0,
+            usecols: None,
+            bool_tokens: None,
         }
     }
+}
 
-    pub fn new(data: Vec<Vec<Cell>>) -> Self {
-        DataFrame { data }
+impl Default for BoolTokens {
+    fn default() -> Self {
+        BoolTokens::new(&["true", "yes", "y", "1"], &["false", "no", "n", "0"])
     }
+}
 
-    pub fn read_from_xlsx(
-        &mut self,
-        path: &str,
-        provided_sheet_name: Option<&str>,
-        provided_with_headers: Option<bool>
-    ) {
-        let mut data_for_dataframe: Vec<Vec<Cell>> = vec![];
-        let sheet_name = provided_sheet_name.unwrap_or("Sheet1");
-        let mut workbook: Xlsx<_> = open_workbook(path).expect("failed to open file");
-
-        if let Ok(range) = workbook.worksheet_range(sheet_name) {
-            for rows in range.rows() {
-                let mut temp_row: Vec<Cell> = vec![];
-                for individual_cell in rows {
-                    // Convert cell data into CellValues
-                    let temp_cell = into_cell_value(individual_cell);
-
-                    if let Some(cell_value) = temp_cell {
-                        temp_row.push(Cell { value: Some(cell_value) });
-                    } else {
-                        // Try to handle as date if not already handled
-                        if let Some(date) = Self::handle_dates(individual_cell) {
-                            temp_row.push(Cell { value: Some(CellValues::Date(date)) });
-                        } else {
-                            // Handle cases where `individual_cell` is `None`
-                            temp_row.push(Cell { value: None });
-                        }
-                    }
-                }
-                // Check the contents of temp_row for debugging
-                data_for_dataframe.push(temp_row);
-            }
+impl BoolTokens {
+    pub fn new(truthy: &[&str], falsy: &[&str]) -> Self {
+        let lower = |tokens: &[&str]| tokens.iter().map(|token| token.trim().to_lowercase()).collect();
+        BoolTokens { truthy: lower(truthy), falsy: lower(falsy) }
+    }
+
+    /// The boolean `value` stands for, `None` when it is neither token set.
+    fn parse(&self, value: &CellValues) -> Option<bool> {
+        if let CellValues::Bool(val) = value {
+            return Some(*val);
+        }
+        let text = value.to_string().trim().to_lowercase();
+        if self.truthy.contains(&text) {
+            Some(true)
+        } else if self.falsy.contains(&text) {
+            Some(false)
         } else {
-            println!("Failed to read worksheet range");
+            None
         }
-        self.data = data_for_dataframe;
     }
 }
 
-impl Date {
-    /// Parses date components based on the given format.
-    fn is_leap_year(year: u32) -> bool {
-        (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+// Builder-style setters, e.g. `ReadOptions::default().sheet("Data").with_headers(true)`
+impl ReadOptions {
+    pub fn sheet(mut self, name: &str) -> Self {
+        self.sheet = Some(name.to_string());
+        self
     }
-    fn days_in_year(year: u16) -> u32 {
-        if Self::is_leap_year(year.into()) { 366 } else { 365 }
+
+    pub fn with_headers(mut self, with_headers: bool) -> Self {
+        self.with_headers = Some(with_headers);
+        self
     }
-    fn days_in_month(year: u16, month: u8) -> u8 {
-        match month {
-            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
-            4 | 6 | 9 | 11 => 30,
-            2 => if Self::is_leap_year(year.into()) { 29 } else { 28 }
-            _ => 0,
-        }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
     }
-    pub fn from_excel_datetype(serial: u32) -> Result<Self, DateParseError> {
-        if serial < 1 {
-            return Err(DateParseError::InvalidSerialNumber);
-        }
 
-        let base_year = 1900;
-        let corrected_serial = if serial > 60 { serial - 1 } else { serial };
-        let days_since_base = corrected_serial - 1;
+    pub fn encoding(mut self, label: &str) -> Self {
+        self.encoding = Some(label.to_string());
+        self
+    }
 
-        let mut year = base_year;
-        let mut days_remaining: u32 = days_since_base;
+    pub fn date_system(mut self, date_system: DateSystem) -> Self {
+        self.date_system = date_system;
+        self
+    }
 
-        while days_remaining >= Self::days_in_year(year) {
-            days_remaining -= Self::days_in_year(year);
-            year += 1;
+    /// Replaces the null sentinels; include `""` to keep reading empty text as null.
+    pub fn null_values(mut self, values: &[&str]) -> Self {
+        self.null_values = values.iter().map(|value| value.to_string()).collect();
+        self
+    }
+
+    /// Forces a column to a type; can be called once per column.
+    pub fn column_type(mut self, col: usize, dtype: DType) -> Self {
+        self.column_types.retain(|(c, _)| *c != col);
+        self.column_types.push((col, dtype));
+        self
+    }
+
+    pub fn number_format(mut self, format: NumberFormat) -> Self {
+        self.number_format = Some(format);
+        self
+    }
+
+    /// Reads text like `"1,234"` as a number by stripping `separator` between
+    /// digit groups. Keeps the decimal separator of an earlier `number_format`,
+    /// `'.'` otherwise. Off by default so text with commas isn't mangled.
+    pub fn thousands_separator(mut self, separator: char) -> Self {
+        let decimal_separator = self.number_format.map_or('.', |format| format.decimal_separator);
+        self.number_format = Some(NumberFormat { decimal_separator, thousands_separator: Some(separator) });
+        self
+    }
+
+    pub fn trim_empty(mut self, trim_empty: bool) -> Self {
+        self.trim_empty = trim_empty;
+        self
+    }
+
+    pub fn keep_errors(mut self, keep_errors: bool) -> Self {
+        self.keep_errors = keep_errors;
+        self
+    }
+
+    pub fn dedupe_headers(mut self, dedupe_headers: bool) -> Self {
+        self.dedupe_headers = dedupe_headers;
+        self
+    }
+
+    pub fn keep_date_serials(mut self, keep_date_serials: bool) -> Self {
+        self.keep_date_serials = keep_date_serials;
+        self
+    }
+
+    pub fn fill_merged(mut self, fill_merged: bool) -> Self {
+        self.fill_merged = fill_merged;
+        self
+    }
+
+    pub fn max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+
+    pub fn skip_rows(mut self, skip_rows: usize) -> Self {
+        self.skip_rows = skip_rows;
+        self
+    }
+
+    pub fn usecols(mut self, cols: &[usize]) -> Self {
+        self.usecols = Some(cols.to_vec());
+        self
+    }
+
+    /// Reads boolean-like columns as Bool using `tokens`, e.g. `BoolTokens::default()`.
+    pub fn bool_tokens(mut self, tokens: BoolTokens) -> Self {
+        self.bool_tokens = Some(tokens);
+        self
+    }
+}
+
+// Same variant and same value. Unlike `f64`, NaN equals NaN here so that a
+// value always equals itself, which map keys like in `value_counts` rely on.
+impl PartialEq for CellValues {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CellValues::Int(x), CellValues::Int(y)) => x == y,
+            (CellValues::Int64(x), CellValues::Int64(y)) => x == y,
+            (CellValues::Float(x), CellValues::Float(y)) => x == y || (x.is_nan() && y.is_nan()),
+            (CellValues::Text(x), CellValues::Text(y)) => x == y,
+            (CellValues::Date(x), CellValues::Date(y)) => x == y,
+            #[cfg(feature = "decimal")]
+            (CellValues::Decimal(x), CellValues::Decimal(y)) => x == y,
+            (CellValues::Bool(x), CellValues::Bool(y)) => x == y,
+            (CellValues::Error(x), CellValues::Error(y)) => x == y,
+            _ => false,
         }
+    }
+}
 
-        let mut month = 1;
-        while days_remaining >= Self::days_in_month(year, month).into() {
-            days_remaining -= Self::days_in_month(year, month) as u32;
-            month += 1;
+// Lets values be used as map keys when counting them
+impl Eq for CellValues {}
+
+impl Hash for CellValues {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            CellValues::Int(val) => val.hash(state),
+            CellValues::Int64(val) => val.hash(state),
+            // 0.0 and -0.0 compare equal, as do all NaNs, so they must hash the same
+            CellValues::Float(val) => {
+                let canonical = if *val == 0.0 { 0.0 } else if val.is_nan() { f64::NAN } else { *val };
+                canonical.to_bits().hash(state)
+            }
+            CellValues::Text(val) => val.hash(state),
+            CellValues::Date(date) => date.hash(state),
+            #[cfg(feature = "decimal")]
+            CellValues::Decimal(val) => val.hash(state),
+            CellValues::Bool(val) => val.hash(state),
+            CellValues::Error(err) => err.hash(state),
         }
+    }
+}
 
-        let day = (days_remaining as u8) + 1;
-        if day > Self::days_in_month(year, month) {
-            return Err(DateParseError::InvalidDate);
+impl Fnv1aHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x100_0000_01b3);
         }
+    }
 
-        Ok(Date { year: year.into(), month: month.into(), day: day.into() })
+    ///lengths go in as u64 so 32 and 64 bit targets agree
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
     }
-    pub fn from_numbers<T>(
-        frag1: T,
-        frag2: T,
-        frag3: T,
-        format: &str
-    ) -> Result<Self, DateParseError>
-        where T: Into<u32>
-    {
-        let months_with_31_days: [u8; 7] = [1, 3, 5, 7, 8, 10, 12];
-        let (year, month, day) = match format {
-            "YYYY/MM/DD" => (frag1.into(), frag2.into() as u8, frag3.into() as u8),
-            "DD/MM/YYYY" => (frag3.into(), frag2.into() as u8, frag1.into() as u8),
-            "MM/DD/YYYY" => (frag3.into(), frag1.into() as u8, frag2.into() as u8),
-            _ => {
-                return Err(DateParseError::UnsupportedFormat);
-            }
+
+    fn write_str(&mut self, text: &str) {
+        self.write_len(text.len());
+        self.write(text.as_bytes());
+    }
+
+    ///one tag byte per variant (0 for null), then the value's bytes
+    fn write_cell(&mut self, value: Option<&CellValues>) {
+        let Some(value) = value else {
+            self.write(&[0]);
+            return;
         };
+        match value {
+            CellValues::Int(val) => {
+                self.write(&[1]);
+                self.write(&val.to_le_bytes());
+            }
+            CellValues::Int64(val) => {
+                self.write(&[2]);
+                self.write(&val.to_le_bytes());
+            }
+            // Same canonical zero and NaN as the `Hash` impl
+            CellValues::Float(val) => {
+                let canonical = if *val == 0.0 { 0.0 } else if val.is_nan() { f64::NAN } else { *val };
+                self.write(&[3]);
+                self.write(&canonical.to_bits().to_le_bytes());
+            }
+            CellValues::Text(val) => {
+                self.write(&[4]);
+                self.write_str(val);
+            }
+            CellValues::Date(date) => {
+                self.write(&[5]);
+                self.write(&date.year.to_le_bytes());
+                self.write(&[date.month, date.day]);
+            }
+            #[cfg(feature = "decimal")]
+            CellValues::Decimal(val) => {
+                let normalized = val.normalized();
+                self.write(&[6]);
+                self.write(&normalized.mantissa.to_le_bytes());
+                self.write(&normalized.scale.to_le_bytes());
+            }
+            CellValues::Bool(val) => self.write(&[7, u8::from(*val)]),
+            CellValues::Error(err) => {
+                self.write(&[8]);
+                self.write_str(err);
+            }
+        }
+    }
 
-        // validation logic
-        if day >= 32 {
-            return Err(DateParseError::InvalidDay);
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Decimal {
+    /// Most digits kept after the decimal point.
+    const MAX_SCALE: u32 = 28;
+
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        Decimal { mantissa, scale }
+    }
+
+    /// Parses plain decimal text like `"-1234.50"`; no exponents or separators.
+    pub fn parse(text: &str) -> Option<Decimal> {
+        let text = text.trim();
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (integer_part, fraction_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if integer_part.is_empty() && fraction_part.is_empty()
+            || !integer_part.chars().chain(fraction_part.chars()).all(|ch| ch.is_ascii_digit())
+            || fraction_part.len() > Self::MAX_SCALE as usize
+        {
+            return None;
         }
-        if !months_with_31_days.contains(&month) && !(day <= 31) {
-            return Err(DateParseError::InvalidDay);
+        let mut mantissa: i128 = 0;
+        for digit in integer_part.bytes().chain(fraction_part.bytes()) {
+            mantissa = mantissa.checked_mul(10)?.checked_add(i128::from(digit - b'0'))?;
         }
-        if month == (2 as u8) {
-            if Self::is_leap_year(year) {
-                if day > 29 {
-                    return Err(DateParseError::InvalidDay);
-                }
-            } else {
-                if day > 28 {
-                    return Err(DateParseError::InvalidDay);
-                }
-            }
+        let mantissa = if negative { -mantissa } else { mantissa };
+        Some(Decimal { mantissa, scale: fraction_part.len() as u32 })
+    }
+
+    /// The shortest decimal that reads back as `val`, so 0.1 becomes exactly 0.1.
+    /// `None` for NaN, infinities and values too large to hold.
+    pub fn from_f64(val: f64) -> Option<Decimal> {
+        if !val.is_finite() {
+            return None;
         }
+        Decimal::parse(&val.to_string())
+    }
 
-        if !(1..=12).contains(&month) {
-            return Err(DateParseError::InvalidMonth);
+    /// Nearest `f64`, e.g. for statistics that work on floats.
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        let (a, b, scale) = self.aligned(other)?;
+        Some(Decimal { mantissa: a.checked_add(b)?, scale })
+    }
+
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        let (a, b, scale) = self.aligned(other)?;
+        Some(Decimal { mantissa: a.checked_sub(b)?, scale })
+    }
+
+    /// `None` on overflow or when the exact product needs more than 28 decimals.
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let product = Decimal {
+            mantissa: self.mantissa.checked_mul(other.mantissa)?,
+            scale: self.scale + other.scale,
+        }.normalized();
+        (product.scale <= Self::MAX_SCALE).then_some(product)
+    }
+
+    /// Same value written with `scale` decimals; `None` if that would drop digits or overflow.
+    fn rescale(self, scale: u32) -> Option<Decimal> {
+        let factor = 10i128.checked_pow(scale.checked_sub(self.scale)?)?;
+        Some(Decimal { mantissa: self.mantissa.checked_mul(factor)?, scale })
+    }
+
+    ///both mantissas at the larger of the two scales
+    fn aligned(self, other: Decimal) -> Option<(i128, i128, u32)> {
+        let scale = self.scale.max(other.scale);
+        Some((self.rescale(scale)?.mantissa, other.rescale(scale)?.mantissa, scale))
+    }
+
+    /// Drops trailing zero decimals, so equal values have one representation.
+    fn normalized(self) -> Decimal {
+        let mut normalized = self;
+        while normalized.scale > 0 && normalized.mantissa % 10 == 0 {
+            normalized.mantissa /= 10;
+            normalized.scale -= 1;
         }
-        if year == 0 {
-            return Err(DateParseError::InvalidYear);
+        normalized
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Eq for Decimal {}
+
+#[cfg(feature = "decimal")]
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.aligned(*other) {
+            Some((a, b, _)) => a.cmp(&b),
+            // Too far apart in scale to line up exactly
+            None => self.to_f64().total_cmp(&other.to_f64()),
         }
+    }
+}
 
-        Ok(Date { year, month, day })
+#[cfg(feature = "decimal")]
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let normalized = self.normalized();
+        normalized.mantissa.hash(state);
+        normalized.scale.hash(state);
     }
 }
 
-impl fmt::Display for DateParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl CellValues {
+    /// Numeric value of the cell, `None` for non-numeric variants.
+    pub fn as_f64(&self) -> Option<f64> {
         match self {
-            DateParseError::UnsupportedFormat => write!(f, "Unsupported date format"),
-            DateParseError::InvalidDay => write!(f, "Invalid day"),
-            DateParseError::InvalidMonth => write!(f, "Invalid month"),
-            DateParseError::InvalidYear => write!(f, "Invalid year"),
-            &DateParseError::InvalidSerialNumber | &DateParseError::InvalidDate => todo!(),
+            CellValues::Int(val) => Some((*val).into()),
+            CellValues::Int64(val) => Some(*val as f64),
+            CellValues::Float(val) => Some(*val),
+            #[cfg(feature = "decimal")]
+            CellValues::Decimal(val) => Some(val.to_f64()),
+            _ => None,
         }
     }
 }
 
-///utils
+impl DataFrame {
+    fn handle_dates(data: &Data, date_system: DateSystem) -> Option<Date> {
+        match data {
+            Data::DateTime(excel_date_time) => {
+                // Check if value can be converted to f64 and then to u32
+                let mut serial_number = excel_date_time.as_f64() as u32;
+                if date_system == DateSystem::Excel1904 {
+                    serial_number += EXCEL_1904_OFFSET;
+                }
+                Date::from_excel_datetype(serial_number).ok()
+            }
+            // ODS stores dates as ISO text, e.g. "2024-04-21" or "2024-04-21T10:30:00"
+            Data::DateTimeIso(iso) => {
+                let mut parts = iso.get(..10)?.split('-').map(|part| part.parse::<u32>().ok());
+                let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+                Date::from_numbers(year, month, day, "YYYY/MM/DD").ok()
+            }
+            _ => None,
+        }
+    }
 
-///assert what is the type of the cell in worksheet
-fn into_cell_value(data: &dyn DataType) -> Option<CellValues> {
-    if let Some(val) = data.get_int() {
-        if val >= (i32::MIN as i64) && val <= (i32::MAX as i64) {
-            return Some(CellValues::Int(val as i32));
+    /// Convert cell data into CellValues; only date cells go through `handle_dates`
+    fn convert_cell(data: &Data, options: &ReadOptions) -> Option<CellValues> {
+        match data {
+            Data::DateTime(excel_date_time) if options.keep_date_serials => {
+                Some(CellValues::Float(excel_date_time.as_f64()))
+            }
+            Data::DateTime(_) | Data::DateTimeIso(_) => {
+                Self::handle_dates(data, options.date_system).map(CellValues::Date)
+            }
+            _ => into_cell_value(data, options),
         }
-    } else if let Some(val) = data.get_float() {
-        return Some(CellValues::Float(val));
-    } else if let Some(val) = data.get_string() {
-        return Some(CellValues::Text(val.to_string()));
-    } else if let Some(val) = data.get_bool() {
-        return Some(CellValues::Text(val.to_string()));
-    } else if data.is_empty() {
-        return None;
-    } else if let Some(err) = data.get_error() {
-        println!("Error in data: {:?}", err);
-        return None;
     }
 
-    None
-}
+    pub fn new(data: Vec<Vec<Cell>>) -> Self {
+        DataFrame { headers: None, data }
+    }
+
+    /// Empties the frame, headers included, so it can be reused for another read.
+    pub fn clear(&mut self) {
+        self.headers = None;
+        self.data.clear();
+    }
+
+    /// Guesses whether the first row is a header row. It is taken as one when
+    /// every non-null cell in it is text and most non-null cells below it are
+    /// numbers or dates. Tables that are text all the way down (or have a
+    /// single row) can't be told apart and give `false`.
+    /// `read_from_xlsx` uses this when `with_headers` is `None`; pass
+    /// `Some(true)`/`Some(false)` to override it.
+    pub fn detect_headers(&self) -> bool {
+        let Some(first_row) = self.data.first() else {
+            return false;
+        };
+        let first_values: Vec<&CellValues> = first_row
+            .iter()
+            .filter_map(|cell| cell.value.as_ref())
+            .collect();
+        if first_values.is_empty() || !first_values.iter().all(|value| matches!(value, CellValues::Text(_))) {
+            return false;
+        }
+        let (mut typed, mut text) = (0, 0);
+        for value in self.data[1..].iter().flatten().filter_map(|cell| cell.value.as_ref()) {
+            match value {
+                CellValues::Text(_) | CellValues::Error(_) => {
+                    text += 1;
+                }
+                _ => {
+                    typed += 1;
+                }
+            }
+        }
+        typed > text
+    }
+
+    /// Returns the position of the column with the given header name.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.headers.as_ref()?.iter().position(|header| header == name)
+    }
+
+    /// Number of columns: the header count, or the widest row for headerless frames.
+    fn width(&self) -> usize {
+        match &self.headers {
+            Some(headers) => headers.len(),
+            None => self.data.iter().map(Vec::len).max().unwrap_or(0),
+        }
+    }
+
+    /// Checks that no two headers share a name. Headerless frames count as unique.
+    pub fn has_unique_headers(&self) -> bool {
+        let mut seen = HashSet::new();
+        self.headers
+            .iter()
+            .flatten()
+            .all(|header| seen.insert(header.as_str()))
+    }
+
+    /// Renames repeated headers to `"Amount"`, `"Amount.1"`, `"Amount.2"`, ...
+    /// skipping any suffix that is already taken by another column.
+    pub fn dedupe_headers(&mut self) {
+        let Some(headers) = self.headers.as_mut() else {
+            return;
+        };
+        let mut taken: HashSet<String> = headers.iter().cloned().collect();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut next_suffix: HashMap<String, usize> = HashMap::new();
+        for header in headers.iter_mut() {
+            if seen.insert(header.clone()) {
+                continue;
+            }
+            let suffix = next_suffix.entry(header.clone()).or_insert(1);
+            let mut candidate = format!("{}.{}", header, suffix);
+            while taken.contains(&candidate) {
+                *suffix += 1;
+                candidate = format!("{}.{}", header, suffix);
+            }
+            *suffix += 1;
+            taken.insert(candidate.clone());
+            seen.insert(candidate.clone());
+            *header = candidate;
+        }
+    }
+
+    /// Gives a headerless frame `col_0`, `col_1`, ... headers (the names used
+    /// when padding a short header row) so name-based methods work on it.
+    /// Frames that already have headers are left alone.
+    pub fn ensure_headers(&mut self) {
+        if self.headers.is_none() {
+            self.headers = Some((0..self.width()).map(|col| format!("col_{}", col)).collect());
+        }
+    }
+
+    /// Renames several columns at once, `old name -> new name`. Everything is
+    /// checked before anything changes: a missing source is
+    /// `DataFrameError::ColumnNotFound`, and a new name that would appear twice
+    /// afterwards is `DuplicateColumn`. Swapping two names is allowed.
+    pub fn rename_columns(&mut self, mapping: &HashMap<String, String>) -> Result<()> {
+        let headers = self.headers.as_mut().ok_or(DataFrameError::MissingHeaders)?;
+        if let Some(missing) = mapping.keys().find(|source| !headers.contains(source)) {
+            return Err(DataFrameError::ColumnNotFound(missing.clone()));
+        }
+        let renamed: Vec<String> = headers
+            .iter()
+            .map(|header| mapping.get(header).unwrap_or(header).clone())
+            .collect();
+        for target in mapping.values() {
+            if renamed.iter().filter(|header| *header == target).count() > 1 {
+                return Err(DataFrameError::DuplicateColumn(target.clone()));
+            }
+        }
+        *headers = renamed;
+        Ok(())
+    }
+
+    /// Number of data rows, not counting the header row.
+    pub fn n_rows(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Number of columns: the header count when headers exist, otherwise the
+    /// width of the first row.
+    pub fn n_cols(&self) -> usize {
+        match &self.headers {
+            Some(headers) => headers.len(),
+            None => self.data.first().map_or(0, Vec::len),
+        }
+    }
+
+    /// Number of distinct rows, without building a deduplicated frame;
+    /// `n_rows() - n_unique_rows()` is the duplicate count. Missing trailing
+    /// cells count as nulls.
+    pub fn n_unique_rows(&self) -> usize {
+        let width = self.width();
+        self.data
+            .iter()
+            .map(|row| {
+                (0..width)
+                    .map(|col| row.get(col).and_then(|cell| cell.value.as_ref()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Hash of the headers and every cell, in order, that stays the same between
+    /// runs, builds and platforms, e.g. to tell whether a cached frame changed. Swapping rows or
+    /// columns changes it; spare capacity and missing trailing cells (read as
+    /// nulls) don't.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1aHasher::default();
+        let width = self.width();
+        match &self.headers {
+            Some(headers) => {
+                hasher.write(&[1]);
+                hasher.write_len(headers.len());
+                headers.iter().for_each(|header| hasher.write_str(header));
+            }
+            None => hasher.write(&[0]),
+        }
+        hasher.write_len(self.data.len());
+        for row in &self.data {
+            for col in 0..width {
+                hasher.write_cell(row.get(col).and_then(|cell| cell.value.as_ref()));
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Returns `(rows, columns)`.
+    pub fn shape(&self) -> (usize, usize) {
+        (self.data.len(), self.width())
+    }
+
+    /// Inferred type of every column, `None` for columns with no values.
+    /// Int and Float mixed together count as Float, other mixes as Text.
+    /// Error cells are ignored.
+    pub fn dtypes(&self) -> Vec<Option<DType>> {
+        (0..self.width())
+            .map(|col| infer_dtype(self.column(col).into_iter().flatten()))
+            .collect()
+    }
+
+    /// Number of null (or missing) cells per column.
+    pub fn null_counts(&self) -> Vec<usize> {
+        (0..self.width())
+            .map(|col| self.column(col).iter().filter(|value| value.is_none()).count())
+            .collect()
+    }
+
+    /// Indices of the columns whose non-null values are all identical,
+    /// including columns that are entirely null. Drop them with `drop_column_at`,
+    /// from the last index down so the others don't shift.
+    pub fn constant_columns(&self) -> Vec<usize> {
+        (0..self.width())
+            .filter(|&col| {
+                let mut values = self.column(col).into_iter().flatten();
+                match values.next() {
+                    Some(first) => values.all(|value| value == first),
+                    None => true,
+                }
+            })
+            .collect()
+    }
+
+    /// Data-quality report with one row per column: name, dtype, non-null
+    /// count, null count, distinct count, min, max and mean. Min and max are
+    /// filled for number and date columns, mean for number columns only;
+    /// metrics that don't apply are null.
+    pub fn profile(&self) -> DataFrame {
+        let headers = ["column", "dtype", "count", "null_count", "distinct", "min", "max", "mean"];
+        let counts = |count: usize| Some(CellValues::from(count as i64));
+        let null_counts = self.null_counts();
+        let data = self
+            .dtypes()
+            .into_iter()
+            .enumerate()
+            .map(|(col, dtype)| {
+                let values = self.column_values(col);
+                let (min, max) = match dtype {
+                    Some(DType::Text | DType::Bool) | None => (None, None),
+                    Some(_) => (
+                        values.iter().min_by(|a, b| compare_values(a, b)).cloned(),
+                        values.iter().max_by(|a, b| compare_values(a, b)).cloned(),
+                    ),
+                };
+                let mean = match dtype {
+                    Some(DType::Text | DType::Date | DType::Bool) | None => None,
+                    Some(_) => self.mean(col).map(CellValues::Float),
+                };
+                [
+                    Some(CellValues::from(self.column_name(col).as_ref())),
+                    dtype.map(|dtype| CellValues::from(format!("{:?}", dtype))),
+                    counts(values.len()),
+                    counts(null_counts[col]),
+                    counts(values.iter().collect::<HashSet<_>>().len()),
+                    min,
+                    max,
+                    mean,
+                ]
+                .into_iter()
+                .map(|value| Cell { value })
+                .collect()
+            })
+            .collect();
+        DataFrame { headers: Some(headers.map(String::from).to_vec()), data }
+    }
+
+    /// Rough memory use of the frame in bytes: the cells themselves plus the
+    /// heap text of Text and Error cells and the headers. Shared (interned)
+    /// strings are counted once per cell.
+    pub fn estimated_size_bytes(&self) -> usize {
+        let headers = self.headers.as_ref().map_or(0, |headers| {
+            headers.iter().map(|header| std::mem::size_of::<String>() + header.capacity()).sum()
+        });
+        let cells: usize = self
+            .data
+            .iter()
+            .map(|row| std::mem::size_of::<Vec<Cell>>() + row.capacity() * std::mem::size_of::<Cell>())
+            .sum();
+        let text: usize = self
+            .data
+            .iter()
+            .flatten()
+            .map(|cell| match &cell.value {
+                Some(CellValues::Text(text)) => text.len(),
+                Some(CellValues::Error(err)) => err.capacity(),
+                _ => 0,
+            })
+            .sum();
+        std::mem::size_of::<DataFrame>() + headers + cells + text
+    }
+
+    /// Summary in the spirit of pandas `.info()`: shape, then name, non-null
+    /// count and dtype of every column, then the estimated memory use.
+    pub fn info(&self) -> String {
+        let mut summary = DataFrame::new(vec![]);
+        summary.headers = Some(["#", "Column", "Non-null", "Dtype"].map(String::from).to_vec());
+        let n_rows = self.n_rows();
+        for (col, (dtype, nulls)) in self.dtypes().into_iter().zip(self.null_counts()).enumerate() {
+            let dtype = dtype.map_or("-".to_string(), |dtype| format!("{:?}", dtype));
+            summary.data.push(
+                [col.to_string(), self.column_name(col).into_owned(), (n_rows - nulls).to_string(), dtype]
+                    .into_iter()
+                    .map(|text| Cell { value: Some(CellValues::from(text)) })
+                    .collect(),
+            );
+        }
+        format!(
+            "DataFrame: {} rows x {} columns\n{}Memory: ~{} bytes\n",
+            n_rows,
+            self.n_cols(),
+            summary,
+            self.estimated_size_bytes()
+        )
+    }
+
+    /// Whether a column with this header name exists.
+    pub fn has_column(&self, name: &str) -> bool {
+        self.column_index(name).is_some()
+    }
+
+    /// Inferred type of the named column, `None` if it is missing or has no values.
+    /// See `dtypes` for how mixed columns are classified.
+    pub fn column_dtype(&self, name: &str) -> Option<DType> {
+        let col = self.column_index(name)?;
+        infer_dtype(self.column(col).into_iter().flatten())
+    }
+
+    /// Checks the frame against an expected `(name, dtype)` schema and reports
+    /// every missing column, extra column and type mismatch, not just the first.
+    /// Columns with no values match any type since there is nothing to check.
+    pub fn validate_schema(&self, expected: &[(&str, DType)]) -> Result<(), Vec<SchemaMismatch>> {
+        let actual: Vec<(String, Option<DType>)> = self
+            .columns()
+            .map(|(name, values)| (name.into_owned(), infer_dtype(values.into_iter().flatten())))
+            .collect();
+        let mut mismatches = vec![];
+        for (name, expected_dtype) in expected {
+            match actual.iter().find(|(actual_name, _)| actual_name == name) {
+                None => mismatches.push(SchemaMismatch::MissingColumn(name.to_string())),
+                Some((_, Some(found))) if found != expected_dtype => {
+                    mismatches.push(SchemaMismatch::TypeMismatch {
+                        column: name.to_string(),
+                        expected: *expected_dtype,
+                        found: *found,
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        for (name, _) in &actual {
+            if !expected.iter().any(|(expected_name, _)| expected_name == name) {
+                mismatches.push(SchemaMismatch::ExtraColumn(name.clone()));
+            }
+        }
+        if mismatches.is_empty() { Ok(()) } else { Err(mismatches) }
+    }
+
+    /// Lists every `(row, col, ours, theirs)` where the two frames disagree.
+    /// Both frames must have the same shape; headers are not compared.
+    pub fn diff<'a>(
+        &'a self,
+        other: &'a DataFrame
+    ) -> Result<Vec<CellDiff<'a>>> {
+        if self.shape() != other.shape() {
+            return Err(DataFrameError::ShapeMismatch(self.shape(), other.shape()));
+        }
+        let mut differences = vec![];
+        for (row_idx, (ours, theirs)) in self.data.iter().zip(&other.data).enumerate() {
+            for col in 0..self.width() {
+                let left = ours.get(col).and_then(|cell| cell.value.as_ref());
+                let right = theirs.get(col).and_then(|cell| cell.value.as_ref());
+                if left != right {
+                    differences.push((row_idx, col, left, right));
+                }
+            }
+        }
+        Ok(differences)
+    }
+
+    /// Like comparing frames with `diff`, but Float cells count as equal when
+    /// they are within `epsilon` of the other number (NaN matches NaN), so
+    /// computed results can be checked without exact float equality.
+    /// Headers and shapes must match exactly.
+    pub fn approx_eq(&self, other: &DataFrame, epsilon: f64) -> bool {
+        if self.headers != other.headers || self.shape() != other.shape() {
+            return false;
+        }
+        self.data.iter().zip(&other.data).all(|(ours, theirs)| {
+            (0..self.width()).all(|col| {
+                let left = ours.get(col).and_then(|cell| cell.value.as_ref());
+                let right = theirs.get(col).and_then(|cell| cell.value.as_ref());
+                match (left, right) {
+                    (Some(a @ CellValues::Float(_)), Some(b)) | (Some(b), Some(a @ CellValues::Float(_))) => {
+                        match (a.as_f64(), b.as_f64()) {
+                            (Some(x), Some(y)) => x == y || (x.is_nan() && y.is_nan()) || (x - y).abs() <= epsilon,
+                            _ => false,
+                        }
+                    }
+                    _ => left == right,
+                }
+            })
+        })
+    }
+
+    /// Returns the values of a column, with `None` for nulls and missing cells.
+    pub fn column(&self, col: usize) -> Vec<Option<&CellValues>> {
+        self.data
+            .iter()
+            .map(|row| row.get(col).and_then(|cell| cell.value.as_ref()))
+            .collect()
+    }
+
+    /// Returns clones of the non-null values of a column, in row order.
+    /// `null_counts` tells how many were left out.
+    pub fn column_values(&self, col: usize) -> Vec<CellValues> {
+        self.column(col).into_iter().flatten().cloned().collect()
+    }
+
+    /// Iterates over `(header, values)` pairs for every column.
+    /// Headerless frames yield the column index as the name, e.g. `"0"`, `"1"`.
+    pub fn columns(&self) -> impl Iterator<Item = (Cow<'_, str>, Vec<Option<&CellValues>>)> {
+        (0..self.width()).map(move |col| (self.column_name(col), self.column(col)))
+    }
+
+    /// Iterates over the rows as cell slices, top to bottom.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.data.iter().map(Vec::as_slice)
+    }
+
+    /// `iter_rows` with each row's index, as used by `take` and friends.
+    pub fn enumerate_rows(&self) -> impl Iterator<Item = (usize, &[Cell])> {
+        self.iter_rows().enumerate()
+    }
+
+    /// Iterates over the rows as typed records through their `FromRow` impl.
+    /// Each row converts on its own, so one bad row doesn't stop the others.
+    pub fn iter_as<T: FromRow>(&self) -> impl Iterator<Item = Result<T, DataFrameError>> + '_ {
+        self.data.iter().map(|row| T::from_row(row))
+    }
+
+    /// Header of a column, or its index as text when there is none.
+    fn column_name(&self, col: usize) -> Cow<'_, str> {
+        match self.headers.as_ref().and_then(|headers| headers.get(col)) {
+            Some(header) => Cow::Borrowed(header.as_str()),
+            None => Cow::Owned(col.to_string()),
+        }
+    }
+
+    /// Inserts a column at `idx`, shifting later columns right.
+    /// `values` needs one entry per row, except on a frame with no columns yet,
+    /// where it sets the row count. `name` is ignored on headerless frames.
+    pub fn insert_column(&mut self, idx: usize, name: &str, values: Vec<CellValues>) -> Result<()> {
+        let width = self.width();
+        if idx > width {
+            return Err(DataFrameError::ColumnOutOfRange(idx));
+        }
+        if width == 0 {
+            self.data.resize_with(values.len(), Vec::new);
+        } else if values.len() != self.data.len() {
+            return Err(DataFrameError::LengthMismatch {
+                expected: self.data.len(),
+                found: values.len(),
+            });
+        }
+
+        if let Some(headers) = self.headers.as_mut() {
+            headers.insert(idx, name.to_string());
+        }
+        for (row, value) in self.data.iter_mut().zip(values) {
+            // Pad short rows so the new cell lands in the right column
+            if row.len() < idx {
+                row.resize_with(idx, || Cell { value: None });
+            }
+            row.insert(idx, Cell { value: Some(value) });
+        }
+        Ok(())
+    }
+
+    /// Appends a column after the last one. See `insert_column`.
+    pub fn add_column(&mut self, name: &str, values: Vec<CellValues>) -> Result<()> {
+        self.insert_column(self.width(), name, values)
+    }
+
+    /// Inserts a row at `idx`, shifting later rows down.
+    /// The row must be as wide as the frame, unless the frame has no columns yet.
+    pub fn insert_row(&mut self, idx: usize, row: Vec<CellValues>) -> Result<()> {
+        if idx > self.data.len() {
+            return Err(DataFrameError::RowOutOfRange(idx));
+        }
+        let width = self.width();
+        if width != 0 && row.len() != width {
+            return Err(DataFrameError::LengthMismatch { expected: width, found: row.len() });
+        }
+        let cells = row
+            .into_iter()
+            .map(|value| Cell { value: Some(value) })
+            .collect();
+        self.data.insert(idx, cells);
+        Ok(())
+    }
+
+    /// Appends a row after the last one. See `insert_row`.
+    pub fn push_row(&mut self, row: Vec<CellValues>) -> Result<()> {
+        self.insert_row(self.data.len(), row)
+    }
+
+    /// Removes the column at `idx` from the headers and every row.
+    pub fn drop_column_at(&mut self, idx: usize) -> Result<()> {
+        if idx >= self.width() {
+            return Err(DataFrameError::ColumnOutOfRange(idx));
+        }
+        if let Some(headers) = self.headers.as_mut() {
+            headers.remove(idx);
+        }
+        for row in &mut self.data {
+            if idx < row.len() {
+                row.remove(idx);
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the named columns. Every name is checked before anything is dropped.
+    pub fn drop_columns(&mut self, names: &[&str]) -> Result<()> {
+        let mut indices = Vec::with_capacity(names.len());
+        for name in names {
+            let idx = self
+                .column_index(name)
+                .ok_or_else(|| DataFrameError::ColumnNotFound(name.to_string()))?;
+            indices.push(idx);
+        }
+        // Drop from the right so earlier indices stay valid
+        indices.sort_unstable();
+        indices.dedup();
+        for idx in indices.into_iter().rev() {
+            self.drop_column_at(idx)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a frame with exactly the `template` columns in that order, e.g. to
+    /// line up files that share columns in different orders before stacking them.
+    /// Template names the frame lacks become all-null columns and columns not in
+    /// the template are dropped.
+    pub fn align_to(&self, template: &[&str]) -> Result<DataFrame> {
+        if self.headers.is_none() {
+            return Err(DataFrameError::MissingHeaders);
+        }
+        let mut sources: Vec<Option<usize>> = Vec::with_capacity(template.len());
+        for (pos, name) in template.iter().enumerate() {
+            if template[..pos].contains(name) {
+                return Err(DataFrameError::DuplicateColumn(name.to_string()));
+            }
+            sources.push(self.column_index(name));
+        }
+        let data = self.data
+            .iter()
+            .map(|row| {
+                sources
+                    .iter()
+                    .map(|source| source.and_then(|col| row.get(col).cloned()).unwrap_or(Cell { value: None }))
+                    .collect()
+            })
+            .collect();
+        let headers = template.iter().map(|name| name.to_string()).collect();
+        Ok(DataFrame { headers: Some(headers), data })
+    }
+
+    /// Moves the named columns to the front, in the given order.
+    /// A partial `order` is allowed: columns it doesn't mention keep their
+    /// relative order after the named ones.
+    pub fn reorder_columns(&mut self, order: &[&str]) -> Result<()> {
+        let headers = self.headers.as_ref().ok_or(DataFrameError::MissingHeaders)?;
+        let mut permutation: Vec<usize> = Vec::with_capacity(headers.len());
+        for name in order {
+            let idx = self
+                .column_index(name)
+                .ok_or_else(|| DataFrameError::ColumnNotFound(name.to_string()))?;
+            if permutation.contains(&idx) {
+                return Err(DataFrameError::DuplicateColumn(name.to_string()));
+            }
+            permutation.push(idx);
+        }
+        for idx in 0..headers.len() {
+            if !permutation.contains(&idx) {
+                permutation.push(idx);
+            }
+        }
+
+        let reordered_headers = permutation
+            .iter()
+            .map(|&idx| headers[idx].clone())
+            .collect();
+        let width = permutation.len();
+        for row in &mut self.data {
+            let mut old_row: Vec<Option<Cell>> = row.drain(..).map(Some).collect();
+            let mut new_row: Vec<Cell> = permutation
+                .iter()
+                .map(|&idx| {
+                    old_row
+                        .get_mut(idx)
+                        .and_then(Option::take)
+                        .unwrap_or(Cell { value: None })
+                })
+                .collect();
+            // Keep any cells past the header width at the end of the row
+            new_row.extend(old_row.into_iter().skip(width).flatten());
+            *row = new_row;
+        }
+        self.headers = Some(reordered_headers);
+        Ok(())
+    }
+
+    /// Replaces every cell in a column equal to `from` with a clone of `to`.
+    /// Passing `None` as `to` turns the matching cells into nulls.
+    /// Returns how many cells were replaced.
+    pub fn replace(&mut self, col: usize, from: &CellValues, to: Option<CellValues>) -> usize {
+        let mut replaced = 0;
+        for row in &mut self.data {
+            if let Some(cell) = row.get_mut(col) {
+                if cell.value.as_ref() == Some(from) {
+                    cell.value = to.clone();
+                    replaced += 1;
+                }
+            }
+        }
+        replaced
+    }
+
+    /// Same as `replace`, but over every column of the frame.
+    pub fn replace_all(&mut self, from: &CellValues, to: Option<CellValues>) -> usize {
+        let mut replaced = 0;
+        for cell in self.data.iter_mut().flatten() {
+            if cell.value.as_ref() == Some(from) {
+                cell.value = to.clone();
+                replaced += 1;
+            }
+        }
+        replaced
+    }
+
+    /// Joins the values of several columns per row into one Text cell.
+    /// Null cells are skipped; a row where every source cell is null yields `None`.
+    pub fn concat_columns(&self, cols: &[usize], sep: &str) -> Vec<Option<CellValues>> {
+        self.data
+            .iter()
+            .map(|row| {
+                let parts: Vec<String> = cols
+                    .iter()
+                    .filter_map(|&col| row.get(col).and_then(|cell| cell.value.as_ref()))
+                    .map(|value| value.to_string())
+                    .collect();
+                if parts.is_empty() { None } else { Some(CellValues::Text(parts.join(sep).into())) }
+            })
+            .collect()
+    }
+
+    /// Splits each text cell of a column into at most `into` parts, returned as
+    /// one vector per part (ready for `add_column`). The last part keeps any
+    /// remaining separators and missing parts are `None`. Non-text values stay
+    /// whole in the first part.
+    pub fn split_column(&self, col: usize, sep: &str, into: usize) -> Vec<Vec<Option<CellValues>>> {
+        let mut parts: Vec<Vec<Option<CellValues>>> = vec![Vec::with_capacity(self.data.len()); into];
+        for row in &self.data {
+            let value = row.get(col).and_then(|cell| cell.value.as_ref());
+            let mut pieces: Vec<Option<CellValues>> = match value {
+                Some(CellValues::Text(text)) => text
+                    .splitn(into, sep)
+                    .map(|piece| Some(CellValues::Text(piece.into())))
+                    .collect(),
+                Some(other) => vec![Some(other.clone())],
+                None => vec![],
+            };
+            pieces.resize(into, None);
+            for (part, piece) in parts.iter_mut().zip(pieces) {
+                part.push(piece);
+            }
+        }
+        parts
+    }
+
+    /// Splits the Text cells of a column on `sep` and emits one row per piece,
+    /// copying the other columns, so `"a;b;c"` becomes three rows.
+    /// Null and non-text cells produce a single unchanged row.
+    pub fn explode(&self, col: usize, sep: &str) -> DataFrame {
+        let mut data = Vec::with_capacity(self.data.len());
+        for row in &self.data {
+            match row.get(col).and_then(|cell| cell.value.as_ref()) {
+                Some(CellValues::Text(text)) => {
+                    for piece in text.split(sep) {
+                        let mut new_row = row.clone();
+                        new_row[col] = Cell { value: Some(CellValues::Text(piece.into())) };
+                        data.push(new_row);
+                    }
+                }
+                _ => data.push(row.clone()),
+            }
+        }
+        DataFrame { headers: self.headers.clone(), data }
+    }
+
+    /// Flattens the given columns into one long column, returned as parallel
+    /// vectors of source column name and value. Traversal is row-major: all of
+    /// `cols` for the first row, then for the second, and so on. Nulls are kept.
+    pub fn stack(&self, cols: &[usize]) -> (Vec<String>, Vec<Option<CellValues>>) {
+        let names: Vec<String> = cols.iter().map(|&col| self.column_name(col).into_owned()).collect();
+        let mut stacked_names = Vec::with_capacity(self.data.len() * cols.len());
+        let mut values = Vec::with_capacity(self.data.len() * cols.len());
+        for row in &self.data {
+            for (name, &col) in names.iter().zip(cols) {
+                stacked_names.push(name.clone());
+                values.push(row.get(col).and_then(|cell| cell.value.clone()));
+            }
+        }
+        (stacked_names, values)
+    }
+
+    /// Trims leading and trailing whitespace from the Text cells of a column.
+    pub fn trim_column(&mut self, col: usize) {
+        self.map_text_column(col, |text| text.trim().to_string());
+    }
+
+    /// Uppercases the Text cells of a column.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_uppercase_column(&mut self, col: usize) {
+        self.map_text_column(col, str::to_uppercase);
+    }
+
+    /// Lowercases the Text cells of a column.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_lowercase_column(&mut self, col: usize) {
+        self.map_text_column(col, str::to_lowercase);
+    }
+
+    /// Left-pads the Text cells of a column with zeros to `width` characters,
+    /// e.g. to restore ZIP codes; a leading sign stays in front (`"-7"` -> `"-007"`).
+    /// Int cells are converted to padded Text as well, since they are usually
+    /// codes that lost their zeros on read. Longer values are left as they are.
+    pub fn zfill_column(&mut self, col: usize, width: usize) {
+        for row in &mut self.data {
+            if let Some(cell) = row.get_mut(col) {
+                let text = match &cell.value {
+                    Some(CellValues::Text(text)) => text.to_string(),
+                    Some(value @ (CellValues::Int(_) | CellValues::Int64(_))) => value.to_string(),
+                    _ => continue,
+                };
+                cell.value = Some(CellValues::Text(zero_pad(&text, width).into()));
+            }
+        }
+    }
+
+    /// Turns whole-valued Float cells of a column (like the `3.0` xlsx stores
+    /// for 3) into Int, or Int64 when they don't fit an i32. Fractional and
+    /// non-finite floats stay as they are. Returns how many cells changed.
+    pub fn downcast_floats(&mut self, col: usize) -> usize {
+        let mut downcast = 0;
+        for row in &mut self.data {
+            if let Some(Cell { value: Some(value) }) = row.get_mut(col) {
+                // i64::MAX itself isn't representable, so the upper bound is exclusive
+                if let CellValues::Float(val) = *value {
+                    if val.fract() == 0.0 && val >= i64::MIN as f64 && val < i64::MAX as f64 {
+                        *value = CellValues::from(val as i64);
+                        downcast += 1;
+                    }
+                }
+            }
+        }
+        downcast
+    }
+
+    /// Converts the cells of a column to `dtype` the way `ReadOptions::column_type`
+    /// does on read, e.g. `DType::Decimal` (with the `decimal` feature) for money
+    /// columns read as floats; a float becomes the shortest decimal that reads
+    /// back as it, so 0.1 is exactly 0.1. Cells that can't be converted become
+    /// nulls; returns how many there were. `DType::Bool` uses `BoolTokens::default()`.
+    pub fn cast_column(&mut self, col: usize, dtype: DType) -> usize {
+        let tokens = BoolTokens::default();
+        let mut failures = 0;
+        for row in &mut self.data {
+            if let Some(cell) = row.get_mut(col) {
+                if let Some(value) = cell.value.take() {
+                    cell.value = coerce_value(value, dtype, &tokens);
+                    if cell.value.is_none() {
+                        failures += 1;
+                    }
+                }
+            }
+        }
+        failures
+    }
+
+    /// Turns every column whose values are all boolean tokens, e.g. a survey
+    /// answer of `"yes"`/`"no"`, into Bool. Columns with any other value, or no
+    /// values, are left alone, so a count column of 0, 1 and 2 stays Int.
+    /// Returns how many columns changed.
+    pub fn infer_bools(&mut self, tokens: &BoolTokens) -> usize {
+        self.infer_bools_except(tokens, &[])
+    }
+
+    /// `infer_bools` that leaves the columns in `skip` alone.
+    fn infer_bools_except(&mut self, tokens: &BoolTokens, skip: &[usize]) -> usize {
+        let mut converted = 0;
+        for col in (0..self.width()).filter(|col| !skip.contains(col)) {
+            let values = self.column(col);
+            let parsed: Option<Vec<Option<bool>>> = values
+                .iter()
+                .map(|value| value.map_or(Some(None), |value| tokens.parse(value).map(Some)))
+                .collect();
+            let Some(parsed) = parsed else {
+                continue;
+            };
+            if parsed.iter().all(Option::is_none) {
+                continue;
+            }
+            for (row, val) in self.data.iter_mut().zip(parsed) {
+                if let (Some(cell), Some(val)) = (row.get_mut(col), val) {
+                    cell.value = Some(CellValues::Bool(val));
+                }
+            }
+            converted += 1;
+        }
+        converted
+    }
+
+    /// Exact sum of the Decimal and integer cells of a column, without going
+    /// through `f64`. Other cells are skipped; `None` when there is nothing to
+    /// add or the sum overflows.
+    #[cfg(feature = "decimal")]
+    pub fn sum_decimal(&self, col: usize) -> Option<Decimal> {
+        let mut values = self.column(col).into_iter().flatten().filter_map(exact_decimal);
+        let first = values.next()?;
+        values.try_fold(first, Decimal::checked_add)
+    }
+
+    /// Turns every cell into Text using its `Display` form, e.g. right before a
+    /// text-only export. Nulls stay null.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_text(&mut self) {
+        for cell in self.data.iter_mut().flatten() {
+            if let Some(value) = &cell.value {
+                if !matches!(value, CellValues::Text(_)) {
+                    cell.value = Some(CellValues::Text(value.to_string().into()));
+                }
+            }
+        }
+    }
+
+    /// Turns the Date cells of a column into Text formatted with `Date::format`,
+    /// e.g. `"DD/MM/YYYY"`. Other cells are left unchanged.
+    pub fn dates_to_text(&mut self, col: usize, fmt: &str) -> Result<()> {
+        if col >= self.width() {
+            return Err(DataFrameError::ColumnOutOfRange(col));
+        }
+        // Check the format even when the column holds no dates
+        date_tokens(fmt)?;
+        for row in &mut self.data {
+            if let Some(cell) = row.get_mut(col) {
+                if let Some(CellValues::Date(date)) = cell.value {
+                    cell.value = Some(CellValues::Text(date.format(fmt)?.into()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the Text cells of a column into Dates with `Date::from_str`.
+    /// Returns `(parsed, failed)`: text that doesn't parse becomes null and is
+    /// counted in `failed` instead of stopping the conversion.
+    pub fn parse_dates(&mut self, col: usize, fmt: &str) -> Result<(usize, usize)> {
+        if col >= self.width() {
+            return Err(DataFrameError::ColumnOutOfRange(col));
+        }
+        date_tokens(fmt)?;
+        let (mut parsed, mut failures) = (0, 0);
+        for row in &mut self.data {
+            if let Some(cell) = row.get_mut(col) {
+                if let Some(CellValues::Text(text)) = &cell.value {
+                    cell.value = match Date::from_str(text, fmt) {
+                        Ok(date) => {
+                            parsed += 1;
+                            Some(CellValues::Date(date))
+                        }
+                        Err(_) => {
+                            failures += 1;
+                            None
+                        }
+                    };
+                }
+            }
+        }
+        Ok((parsed, failures))
+    }
+
+    /// Fills nulls in a column with the last non-null value above them, e.g.
+    /// the blanks under a merged cell. Nulls before the first value stay null.
+    pub fn ffill_column(&mut self, col: usize) {
+        let mut last: Option<CellValues> = None;
+        for row in &mut self.data {
+            if let Some(cell) = row.get_mut(col) {
+                match &cell.value {
+                    Some(value) => last = Some(value.clone()),
+                    None => cell.value = last.clone(),
+                }
+            }
+        }
+    }
+
+    /// Fills nulls in a column with the next non-null value below them, the
+    /// reverse of `ffill_column`. Nulls after the last value stay null.
+    pub fn bfill_column(&mut self, col: usize) {
+        let mut next: Option<CellValues> = None;
+        for row in self.data.iter_mut().rev() {
+            if let Some(cell) = row.get_mut(col) {
+                match &cell.value {
+                    Some(value) => next = Some(value.clone()),
+                    None => cell.value = next.clone(),
+                }
+            }
+        }
+    }
+
+    /// Rewrites the Text cells of a column, leaving every other cell alone.
+    fn map_text_column<F>(&mut self, col: usize, f: F) where F: Fn(&str) -> String {
+        for row in &mut self.data {
+            if let Some(Cell { value: Some(CellValues::Text(text)) }) = row.get_mut(col) {
+                *text = f(text).into();
+            }
+        }
+    }
+
+    /// Returns a new frame with the rows at `indices`, in that order.
+    /// Repeats are allowed and out-of-range indices are skipped.
+    pub fn take(&self, indices: &[usize]) -> DataFrame {
+        let data = indices
+            .iter()
+            .filter_map(|&idx| self.data.get(idx).cloned())
+            .collect();
+        DataFrame { headers: self.headers.clone(), data }
+    }
+
+    /// Splits the rows into frames of at most `size` rows, each with the headers,
+    /// e.g. for batch processing. The last chunk may be smaller and a frame
+    /// without rows gives no chunks. A `size` of 0 gives the whole frame as one chunk.
+    pub fn chunks(&self, size: usize) -> Vec<DataFrame> {
+        if size == 0 {
+            return vec![DataFrame { headers: self.headers.clone(), data: self.data.clone() }];
+        }
+        self.data
+            .chunks(size)
+            .map(|rows| DataFrame { headers: self.headers.clone(), data: rows.to_vec() })
+            .collect()
+    }
+
+    /// Splits the rows into one frame per value of `col`, keyed by the value as
+    /// text and each with the headers, e.g. before writing a file per category.
+    /// Rows keep their order within a part. Nulls and rows too short to have
+    /// the column go under `NULL_PARTITION_KEY` (`"<null>"`). So that no value
+    /// can land there too, a value whose text is `"<null>"` or starts with a
+    /// backslash is keyed with one extra leading backslash (`"\\<null>"`).
+    pub fn partition_by(&self, col: usize) -> HashMap<String, DataFrame> {
+        let mut parts: HashMap<String, DataFrame> = HashMap::new();
+        for row in &self.data {
+            let key = match row.get(col).and_then(|cell| cell.value.as_ref()) {
+                Some(value) => {
+                    let key = value.to_string();
+                    if key == NULL_PARTITION_KEY || key.starts_with('\\') { format!("\\{}", key) } else { key }
+                }
+                None => NULL_PARTITION_KEY.to_string(),
+            };
+            parts
+                .entry(key)
+                .or_insert_with(|| DataFrame { headers: self.headers.clone(), data: vec![] })
+                .data
+                .push(row.clone());
+        }
+        parts
+    }
+
+    /// Row indices that would sort the frame by a column, without moving any rows.
+    /// The sort is stable and nulls go last in either direction.
+    pub fn argsort(&self, col: usize, ascending: bool) -> Vec<usize> {
+        let values = self.column(col);
+        let mut indices: Vec<usize> = (0..self.data.len()).collect();
+        indices.sort_by(|&a, &b| compare_cells(values[a], values[b], ascending));
+        indices
+    }
+
+    /// Sorts the rows by several `(column, ascending)` keys in priority order:
+    /// ties on the first key are broken by the second, and so on.
+    /// Uses the same ordering as `argsort` at every level.
+    pub fn sort_by_columns(&mut self, cols: &[(usize, bool)]) {
+        self.data.sort_by(|a, b| {
+            cols.iter().fold(Ordering::Equal, |ordering, &(col, ascending)| {
+                ordering.then_with(|| {
+                    let left = a.get(col).and_then(|cell| cell.value.as_ref());
+                    let right = b.get(col).and_then(|cell| cell.value.as_ref());
+                    compare_cells(left, right, ascending)
+                })
+            })
+        });
+    }
+
+    /// Builds a row mask of Text cells in a column that contain `needle`.
+    /// Non-text and null cells are `false`.
+    pub fn str_contains(&self, col: usize, needle: &str, case_insensitive: bool) -> Vec<bool> {
+        let needle = if case_insensitive { needle.to_lowercase() } else { needle.to_string() };
+        self.column(col)
+            .into_iter()
+            .map(|value| match value {
+                Some(CellValues::Text(text)) if case_insensitive => text.to_lowercase().contains(&needle),
+                Some(CellValues::Text(text)) => text.contains(&needle),
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Returns a new frame with the rows whose mask entry is `true`.
+    /// Rows past the end of the mask are dropped.
+    pub fn filter_mask(&self, mask: &[bool]) -> DataFrame {
+        let data = self.data
+            .iter()
+            .zip(mask)
+            .filter(|(_, keep)| **keep)
+            .map(|(row, _)| row.clone())
+            .collect();
+        DataFrame { headers: self.headers.clone(), data }
+    }
+
+    /// Returns a new frame without the rows that have any null cell.
+    /// Cells missing from short rows count as nulls.
+    pub fn dropna(&self) -> DataFrame {
+        self.dropna_thresh(self.width())
+    }
+
+    /// Returns a new frame keeping only the rows with at least `min_non_null`
+    /// non-null cells, like pandas `dropna(thresh=...)`.
+    pub fn dropna_thresh(&self, min_non_null: usize) -> DataFrame {
+        let width = self.width();
+        let data = self.data
+            .iter()
+            .filter(|row| row.iter().take(width).filter(|cell| cell.value.is_some()).count() >= min_non_null)
+            .cloned()
+            .collect();
+        DataFrame { headers: self.headers.clone(), data }
+    }
+
+    /// Dictionary-encodes the Text cells of a column: equal strings end up
+    /// sharing a single allocation. Values read back exactly as before.
+    /// Returns the number of distinct strings kept.
+    pub fn intern_column(&mut self, col: usize) -> usize {
+        let mut dictionary: HashSet<Arc<str>> = HashSet::new();
+        for row in &mut self.data {
+            if let Some(Cell { value: Some(CellValues::Text(text)) }) = row.get_mut(col) {
+                match dictionary.get(text) {
+                    Some(shared) => {
+                        *text = Arc::clone(shared);
+                    }
+                    None => {
+                        dictionary.insert(Arc::clone(text));
+                    }
+                }
+            }
+        }
+        dictionary.len()
+    }
+
+    /// Returns the earliest and latest date in a column.
+    /// Nulls and non-date cells are ignored; `None` if the column holds no dates.
+    pub fn date_range(&self, col: usize) -> Option<(Date, Date)> {
+        let mut dates = self.data.iter().filter_map(|row| match row.get(col) {
+            Some(Cell { value: Some(CellValues::Date(date)) }) => Some(*date),
+            _ => None,
+        });
+        let first = dates.next()?;
+        Some(dates.fold((first, first), |(min, max), date| (min.min(date), max.max(date))))
+    }
+
+    /// Returns a column as numbers; nulls and non-numeric cells are `None`.
+    pub fn column_as_f64(&self, col: usize) -> Vec<Option<f64>> {
+        self.column_view_f64(col).iter().collect()
+    }
+
+    /// Borrows a column as numbers without allocating, unlike `column_as_f64`.
+    /// Handy when running several passes over the same column of a large frame.
+    pub fn column_view_f64(&self, col: usize) -> ColumnView<'_> {
+        ColumnView { frame: self, col }
+    }
+
+    /// Folds over a column top to bottom, for aggregations the frame doesn't
+    /// provide, e.g. a geometric mean. Nulls are visited as `None`.
+    pub fn reduce<T, F>(&self, col: usize, init: T, f: F) -> T
+        where F: Fn(T, Option<&CellValues>) -> T
+    {
+        self.data
+            .iter()
+            .map(|row| row.get(col).and_then(|cell| cell.value.as_ref()))
+            .fold(init, f)
+    }
+
+    /// Builds a new frame by transforming whole columns at once, e.g. to
+    /// normalise each one. `f` gets the column index and its cells and must
+    /// return one value per row, else `DataFrameError::LengthMismatch`.
+    /// Headers are kept as they are.
+    pub fn apply_columns<F>(&self, f: F) -> Result<DataFrame>
+        where F: Fn(usize, &[Option<&CellValues>]) -> Vec<CellValues>
+    {
+        let height = self.data.len();
+        let mut data: Vec<Vec<Cell>> = (0..height).map(|_| Vec::with_capacity(self.width())).collect();
+        for col in 0..self.width() {
+            let values = f(col, &self.column(col));
+            if values.len() != height {
+                return Err(DataFrameError::LengthMismatch { expected: height, found: values.len() });
+            }
+            for (row, value) in data.iter_mut().zip(values) {
+                row.push(Cell { value: Some(value) });
+            }
+        }
+        Ok(DataFrame { headers: self.headers.clone(), data })
+    }
+
+    /// Arithmetic mean of the numeric cells of a column.
+    /// `None` means there was nothing to average: either the frame has no rows
+    /// or every cell is null/non-numeric. Use `mean_checked` to tell those apart.
+    pub fn mean(&self, col: usize) -> Option<f64> {
+        let (sum, count) = self
+            .column_view_f64(col)
+            .iter()
+            .flatten()
+            .fold((0.0, 0usize), |(sum, count), val| (sum + val, count + 1));
+        if count == 0 {
+            return None;
+        }
+        Some(sum / (count as f64))
+    }
+
+    /// `mean`, with `default` in place of `None`.
+    pub fn mean_or(&self, col: usize, default: f64) -> f64 {
+        self.mean(col).unwrap_or(default)
+    }
+
+    /// `mean` that says why there is no value: `DataFrameError::NoRows` for an
+    /// empty frame, `NoNumericValues` when rows exist but none are numeric.
+    pub fn mean_checked(&self, col: usize) -> Result<f64> {
+        if col >= self.width() {
+            return Err(DataFrameError::ColumnOutOfRange(col));
+        }
+        if self.data.is_empty() {
+            return Err(DataFrameError::NoRows);
+        }
+        self.mean(col).ok_or(DataFrameError::NoNumericValues(col))
+    }
+
+    /// Computes the `q`-th quantile (0.0 to 1.0) of a numeric column, linearly
+    /// interpolating between sorted values. Nulls and non-numeric cells are skipped.
+    /// Returns `None` if `q` is out of range or the column has no numbers.
+    pub fn quantile(&self, col: usize, q: f64) -> Option<f64> {
+        if !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let mut values: Vec<f64> = self
+            .column_as_f64(col)
+            .into_iter()
+            .flatten()
+            .filter(|val| !val.is_nan())
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+
+        let position = q * ((values.len() - 1) as f64);
+        let lower = position.floor() as usize;
+        let upper = position.ceil() as usize;
+        let fraction = position - (lower as f64);
+        Some(values[lower] + (values[upper] - values[lower]) * fraction)
+    }
+
+    /// Median of a numeric column, i.e. `quantile(col, 0.5)`.
+    pub fn median(&self, col: usize) -> Option<f64> {
+        self.quantile(col, 0.5)
+    }
+
+    /// Most frequent non-null value of a column of any type.
+    /// Ties go to the value seen first.
+    pub fn mode(&self, col: usize) -> Option<CellValues> {
+        // value -> (count, first row it appeared in)
+        let mut counts: HashMap<&CellValues, (usize, usize)> = HashMap::new();
+        for (row_idx, value) in self.column(col).into_iter().enumerate() {
+            if let Some(value) = value {
+                counts.entry(value).or_insert((0, row_idx)).0 += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .max_by(|(_, (count_a, first_a)), (_, (count_b, first_b))| {
+                count_a.cmp(count_b).then(first_b.cmp(first_a))
+            })
+            .map(|(value, _)| value.clone())
+    }
+
+    /// Counts each distinct value of a column, most frequent first; ties keep
+    /// the order in which the values first appear. Nulls are not counted.
+    pub fn value_counts(&self, col: usize) -> Vec<(CellValues, usize)> {
+        let mut order: Vec<&CellValues> = Vec::new();
+        let mut counts: HashMap<&CellValues, usize> = HashMap::new();
+        for value in self.column(col).into_iter().flatten() {
+            let count = counts.entry(value).or_insert(0);
+            if *count == 0 {
+                order.push(value);
+            }
+            *count += 1;
+        }
+        let mut result: Vec<(CellValues, usize)> =
+            order.into_iter().map(|value| (value.clone(), counts[value])).collect();
+        // Stable sort, so equal counts stay in order of appearance
+        result.sort_by(|(_, a), (_, b)| b.cmp(a));
+        result
+    }
+
+    /// Frequency table of two columns: one row per distinct `row_col` value,
+    /// one column per distinct `col_col` value, and Int counts in the cells.
+    /// Categories are sorted like `sort_by_columns`; rows where either value
+    /// is null are left out.
+    pub fn crosstab(&self, row_col: usize, col_col: usize) -> DataFrame {
+        let sorted_categories = |col: usize| {
+            let mut values: Vec<CellValues> =
+                self.value_counts(col).into_iter().map(|(value, _)| value).collect();
+            values.sort_by(compare_values);
+            values
+        };
+        let row_values = sorted_categories(row_col);
+        let col_values = sorted_categories(col_col);
+        let row_index: HashMap<&CellValues, usize> =
+            row_values.iter().enumerate().map(|(idx, value)| (value, idx)).collect();
+        let col_index: HashMap<&CellValues, usize> =
+            col_values.iter().enumerate().map(|(idx, value)| (value, idx)).collect();
+
+        let mut counts = vec![vec![0i32; col_values.len()]; row_values.len()];
+        for row in &self.data {
+            let value_at = |col: usize| row.get(col).and_then(|cell| cell.value.as_ref());
+            if let (Some(a), Some(b)) = (value_at(row_col), value_at(col_col)) {
+                counts[row_index[a]][col_index[b]] += 1;
+            }
+        }
+
+        let mut headers = vec![self.column_name(row_col).into_owned()];
+        headers.extend(col_values.iter().map(ToString::to_string));
+        let data = row_values
+            .iter()
+            .zip(counts)
+            .map(|(value, row_counts)| {
+                let mut row = vec![Cell { value: Some(value.clone()) }];
+                row.extend(row_counts.into_iter().map(|count| Cell { value: Some(CellValues::Int(count)) }));
+                row
+            })
+            .collect();
+        DataFrame { headers: Some(headers), data }
+    }
+
+    /// Replaces a categorical column with one Int 0/1 indicator column per
+    /// distinct value, named after the value and sorted like `crosstab`.
+    /// Nulls give a row of zeros; the other columns are kept as they are.
+    pub fn one_hot(&self, col: usize) -> DataFrame {
+        let mut categories: Vec<CellValues> =
+            self.value_counts(col).into_iter().map(|(value, _)| value).collect();
+        categories.sort_by(compare_values);
+
+        let headers = self.headers.as_ref().map(|headers| {
+            let mut new_headers = headers.clone();
+            if col < new_headers.len() {
+                new_headers.splice(col..=col, categories.iter().map(ToString::to_string));
+            }
+            new_headers
+        });
+        let data = self
+            .data
+            .iter()
+            .map(|row| {
+                let value = row.get(col).and_then(|cell| cell.value.as_ref());
+                let indicators = categories.iter().map(|category| Cell {
+                    value: Some(CellValues::Int(i32::from(value == Some(category)))),
+                });
+                let mut new_row = row.clone();
+                if col < new_row.len() {
+                    new_row.splice(col..=col, indicators);
+                }
+                new_row
+            })
+            .collect();
+        DataFrame { headers, data }
+    }
+
+    /// Variance of a numeric column, skipping nulls and non-numeric cells.
+    /// `sample` divides by n-1 instead of n and then needs at least two values.
+    pub fn variance(&self, col: usize, sample: bool) -> Option<f64> {
+        let values: Vec<f64> = self.column_as_f64(col).into_iter().flatten().collect();
+        let n = values.len();
+        let required = if sample { 2 } else { 1 };
+        if n < required {
+            return None;
+        }
+        let mean = values.iter().sum::<f64>() / (n as f64);
+        let squared_deviations: f64 = values
+            .iter()
+            .map(|val| (val - mean).powi(2))
+            .sum();
+        let divisor = if sample { n - 1 } else { n };
+        Some(squared_deviations / (divisor as f64))
+    }
+
+    /// Standard deviation of a numeric column, see `variance`.
+    pub fn std(&self, col: usize, sample: bool) -> Option<f64> {
+        self.variance(col, sample).map(f64::sqrt)
+    }
+
+    /// `column_as_f64` with an explicit `CoercionPolicy` for non-numeric cells:
+    /// `Skip` gives `None` in their place, so the result still lines up with
+    /// the rows, and `Error` fails on the first.
+    pub fn column_as_f64_with(&self, col: usize, policy: CoercionPolicy) -> Result<Vec<Option<f64>>> {
+        let mut values = Vec::with_capacity(self.data.len());
+        for (row, value) in self.column(col).into_iter().enumerate() {
+            match value.map(|value| value.as_f64()) {
+                None => values.push(None),
+                Some(Some(val)) => values.push(Some(val)),
+                Some(None) => match policy {
+                    CoercionPolicy::Skip => values.push(None),
+                    CoercionPolicy::Error => return Err(DataFrameError::NonNumeric { col, row }),
+                },
+            }
+        }
+        Ok(values)
+    }
+
+    /// Strict `column_as_f64`: every cell must be a number. Reports the first
+    /// offending cell as `DataFrameError::NullValue` or `NonNumeric`, and an
+    /// unknown column as `ColumnOutOfRange`.
+    pub fn try_column_as_f64(&self, col: usize) -> Result<Vec<f64>> {
+        if col >= self.width() {
+            return Err(DataFrameError::ColumnOutOfRange(col));
+        }
+        self.column(col)
+            .into_iter()
+            .enumerate()
+            .map(|(row, value)| match value {
+                None => Err(DataFrameError::NullValue { col, row }),
+                Some(value) => value.as_f64().ok_or(DataFrameError::NonNumeric { col, row }),
+            })
+            .collect()
+    }
+
+    /// Fails under `CoercionPolicy::Error` when a column holds a non-numeric cell.
+    /// `Skip` leaves such cells out of an aggregate.
+    fn check_numeric(&self, col: usize, policy: CoercionPolicy) -> Result<()> {
+        if policy == CoercionPolicy::Error {
+            self.column_as_f64_with(col, policy)?;
+        }
+        Ok(())
+    }
+
+    /// `mean` with a `CoercionPolicy` for non-numeric cells.
+    pub fn mean_with(&self, col: usize, policy: CoercionPolicy) -> Result<Option<f64>> {
+        self.check_numeric(col, policy)?;
+        Ok(self.mean(col))
+    }
+
+    /// `quantile` with a `CoercionPolicy` for non-numeric cells.
+    pub fn quantile_with(&self, col: usize, q: f64, policy: CoercionPolicy) -> Result<Option<f64>> {
+        self.check_numeric(col, policy)?;
+        Ok(self.quantile(col, q))
+    }
+
+    /// `median` with a `CoercionPolicy` for non-numeric cells.
+    pub fn median_with(&self, col: usize, policy: CoercionPolicy) -> Result<Option<f64>> {
+        self.check_numeric(col, policy)?;
+        Ok(self.median(col))
+    }
+
+    /// `variance` with a `CoercionPolicy` for non-numeric cells.
+    pub fn variance_with(&self, col: usize, sample: bool, policy: CoercionPolicy) -> Result<Option<f64>> {
+        self.check_numeric(col, policy)?;
+        Ok(self.variance(col, sample))
+    }
+
+    /// `std` with a `CoercionPolicy` for non-numeric cells.
+    pub fn std_with(&self, col: usize, sample: bool, policy: CoercionPolicy) -> Result<Option<f64>> {
+        self.check_numeric(col, policy)?;
+        Ok(self.std(col, sample))
+    }
+
+    /// `correlation` with a `CoercionPolicy` applied to both columns.
+    pub fn correlation_with(&self, a: usize, b: usize, policy: CoercionPolicy) -> Result<Option<f64>> {
+        self.check_numeric(a, policy)?;
+        self.check_numeric(b, policy)?;
+        Ok(self.correlation(a, b))
+    }
+
+    /// `row_sum` with a `CoercionPolicy` applied to every listed column.
+    pub fn row_sum_with(&self, cols: &[usize], policy: CoercionPolicy) -> Result<Vec<Option<f64>>> {
+        for &col in cols {
+            self.check_numeric(col, policy)?;
+        }
+        Ok(self.row_sum(cols))
+    }
+
+    /// `row_mean` with a `CoercionPolicy` applied to every listed column.
+    pub fn row_mean_with(&self, cols: &[usize], policy: CoercionPolicy) -> Result<Vec<Option<f64>>> {
+        for &col in cols {
+            self.check_numeric(col, policy)?;
+        }
+        Ok(self.row_mean(cols))
+    }
+
+    /// Pearson correlation between two numeric columns, using only rows where
+    /// both cells are numeric. `None` with fewer than two such rows or when
+    /// either column is constant over them.
+    pub fn correlation(&self, a: usize, b: usize) -> Option<f64> {
+        let pairs: Vec<(f64, f64)> = self
+            .column_as_f64(a)
+            .into_iter()
+            .zip(self.column_as_f64(b))
+            .filter_map(|(x, y)| Some((x?, y?)))
+            .collect();
+        if pairs.len() < 2 {
+            return None;
+        }
+        let n = pairs.len() as f64;
+        let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let (mut covariance, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+        for (x, y) in &pairs {
+            covariance += (x - mean_x) * (y - mean_y);
+            var_x += (x - mean_x).powi(2);
+            var_y += (y - mean_y).powi(2);
+        }
+        if var_x == 0.0 || var_y == 0.0 {
+            return None;
+        }
+        Some(covariance / (var_x.sqrt() * var_y.sqrt()))
+    }
+
+    /// Ranks the numeric cells of a column from 1, smallest first unless
+    /// `ascending` is false. Ties share the average of the ranks they span, so
+    /// `[10, 20, 20, 30]` ranks as `[1, 2.5, 2.5, 4]`. Nulls, NaN and non-numeric
+    /// cells get `None` and don't take up a rank.
+    pub fn rank(&self, col: usize, ascending: bool) -> Vec<Option<f64>> {
+        let values = self.column_as_f64(col);
+        let mut order: Vec<(usize, f64)> = values
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, value)| value.filter(|val| !val.is_nan()).map(|val| (idx, val)))
+            .collect();
+        order.sort_by(|(_, a), (_, b)| {
+            let ordering = a.total_cmp(b);
+            if ascending { ordering } else { ordering.reverse() }
+        });
+
+        let mut ranks = vec![None; values.len()];
+        let mut start = 0;
+        while start < order.len() {
+            let mut end = start;
+            while end + 1 < order.len() && order[end + 1].1 == order[start].1 {
+                end += 1;
+            }
+            // Positions start..=end hold ranks start + 1 ..= end + 1
+            let average = (start + end) as f64 / 2.0 + 1.0;
+            for (idx, _) in &order[start..=end] {
+                ranks[*idx] = Some(average);
+            }
+            start = end + 1;
+        }
+        ranks
+    }
+
+    /// Buckets the numeric cells of a column into bins between consecutive
+    /// `bins` edges (ascending). Bins are right-closed, `(a, b]`, except that the
+    /// first one also takes its lower edge, so edges `[0, 18, 65]` put 0 and 18
+    /// in the first bin. Each value becomes its bin's label as Text, or the bin
+    /// index as Int when `labels` is `None`. Nulls, non-numeric cells and values
+    /// outside every bin give `None`. Edges that aren't strictly increasing give
+    /// `DataFrameError::UnsortedBins`, and labels that aren't one per bin
+    /// `DataFrameError::LengthMismatch`.
+    pub fn cut(&self, col: usize, bins: &[f64], labels: Option<&[&str]>) -> Result<Vec<Option<CellValues>>> {
+        // NaN edges compare as unordered and are rejected too
+        if bins.windows(2).any(|edges| edges[0].partial_cmp(&edges[1]) != Some(Ordering::Less)) {
+            return Err(DataFrameError::UnsortedBins);
+        }
+        if let Some(labels) = labels {
+            let expected = bins.len().saturating_sub(1);
+            if labels.len() != expected {
+                return Err(DataFrameError::LengthMismatch { expected, found: labels.len() });
+            }
+        }
+        Ok(self.column_view_f64(col)
+            .iter()
+            .map(|value| {
+                let val = value?;
+                let bin = bins.windows(2).enumerate().position(|(idx, edges)| {
+                    (val > edges[0] || (idx == 0 && val == edges[0])) && val <= edges[1]
+                })?;
+                Some(match labels {
+                    Some(labels) => CellValues::from(labels[bin]),
+                    None => CellValues::Int(bin as i32),
+                })
+            })
+            .collect())
+    }
+
+    /// Difference between each numeric value of a column and the one `periods`
+    /// rows earlier, e.g. day-over-day change on a daily series. The first
+    /// `periods` positions, and any pair with a null or non-numeric cell, are `None`.
+    pub fn diff_column(&self, col: usize, periods: usize) -> Vec<Option<f64>> {
+        let values = self.column_as_f64(col);
+        let earlier = shift_values(&values, i64::try_from(periods).unwrap_or(i64::MAX));
+        values
+            .into_iter()
+            .zip(earlier)
+            .map(|(value, earlier)| Some(value? - earlier?))
+            .collect()
+    }
+
+    /// Relative change `(v[i] - v[i - periods]) / v[i - periods]` over a numeric
+    /// column, e.g. period returns of a price series. Leading positions, nulls,
+    /// non-numeric cells and a zero earlier value give `None`.
+    pub fn pct_change(&self, col: usize, periods: usize) -> Vec<Option<f64>> {
+        let values = self.column_as_f64(col);
+        let earlier = shift_values(&values, i64::try_from(periods).unwrap_or(i64::MAX));
+        values
+            .into_iter()
+            .zip(earlier)
+            .map(|(value, earlier)| {
+                let earlier = earlier.filter(|earlier| *earlier != 0.0)?;
+                Some((value? - earlier) / earlier)
+            })
+            .collect()
+    }
+
+    /// Runs `f` over every window of `window` consecutive values of a numeric
+    /// column, e.g. a weighted moving average. Output `i` is `f` of the window
+    /// ending at row `i`; the first `window - 1` outputs are `None`, as are all
+    /// of them for a `window` of 0. Nulls and non-numeric cells reach `f` as `None`.
+    pub fn rolling_apply<F>(&self, col: usize, window: usize, f: F) -> Vec<Option<f64>>
+        where F: Fn(&[Option<f64>]) -> Option<f64>
+    {
+        let values = self.column_as_f64(col);
+        let mut results: Vec<Option<f64>> = vec![None; values.len()];
+        if window == 0 {
+            return results;
+        }
+        for (end, values) in values.windows(window).enumerate() {
+            results[end + window - 1] = f(values);
+        }
+        results
+    }
+
+    /// Values of a column moved down by `periods` rows (up when negative), with
+    /// `None` in the vacated positions; the frame itself is not changed.
+    /// Handy for lag features, and what `diff_column` and `pct_change` build on.
+    pub fn shift_column(&self, col: usize, periods: i64) -> Vec<Option<CellValues>> {
+        let values: Vec<Option<CellValues>> = self.column(col).into_iter().map(|value| value.cloned()).collect();
+        shift_values(&values, periods)
+    }
+
+    /// Sum across the given columns for each row, skipping nulls and
+    /// non-numeric cells. A row with no numbers in those columns gives `None`.
+    /// The result can be attached with `add_column`.
+    pub fn row_sum(&self, cols: &[usize]) -> Vec<Option<f64>> {
+        self.row_numbers(cols)
+            .map(|values| if values.is_empty() { None } else { Some(values.iter().sum()) })
+            .collect()
+    }
+
+    /// Mean across the given columns for each row, like `row_sum`.
+    pub fn row_mean(&self, cols: &[usize]) -> Vec<Option<f64>> {
+        self.row_numbers(cols)
+            .map(|values| {
+                if values.is_empty() {
+                    None
+                } else {
+                    Some(values.iter().sum::<f64>() / (values.len() as f64))
+                }
+            })
+            .collect()
+    }
+
+    /// Numeric values of the given columns, row by row.
+    fn row_numbers<'a>(&'a self, cols: &'a [usize]) -> impl Iterator<Item = Vec<f64>> + 'a {
+        self.data.iter().map(move |row| {
+            cols.iter()
+                .filter_map(|&col| row.get(col)?.value.as_ref()?.as_f64())
+                .collect()
+        })
+    }
+
+    /// Inner join: one row per pair of rows whose key cells are equal.
+    /// The result holds every left column followed by the right columns
+    /// except the right key; right names the left side already has get
+    /// `JOIN_RIGHT_SUFFIX` (`"_right"`). Null keys never match.
+    pub fn join(&self, other: &DataFrame, left_col: usize, right_col: usize) -> DataFrame {
+        self.join_rows(other, left_col, right_col, false, false)
+    }
+
+    /// Left join: like `join`, but left rows without a match are kept once
+    /// with every right column set to null.
+    pub fn join_left(&self, other: &DataFrame, left_col: usize, right_col: usize) -> DataFrame {
+        self.join_rows(other, left_col, right_col, true, false)
+    }
+
+    /// Outer join: like `join_left`, and right rows that matched nothing are
+    /// appended with the left columns null, except the left key column, which
+    /// takes the right key so the row can still be identified.
+    pub fn join_outer(&self, other: &DataFrame, left_col: usize, right_col: usize) -> DataFrame {
+        self.join_rows(other, left_col, right_col, true, true)
+    }
+
+    fn join_rows(
+        &self,
+        other: &DataFrame,
+        left_col: usize,
+        right_col: usize,
+        keep_left: bool,
+        keep_right: bool
+    ) -> DataFrame {
+        let left_width = self.width();
+        let right_width = other.width();
+        let headers = if self.headers.is_none() && other.headers.is_none() {
+            None
+        } else {
+            let mut names: Vec<String> = self.columns().map(|(name, _)| name.into_owned()).collect();
+            for (_, (name, _)) in other.columns().enumerate().filter(|(col, _)| *col != right_col) {
+                let mut name = name.into_owned();
+                while names.contains(&name) {
+                    name.push_str(JOIN_RIGHT_SUFFIX);
+                }
+                names.push(name);
+            }
+            Some(names)
+        };
+
+        // Right rows by key, in row order
+        let mut right_by_key: HashMap<&CellValues, Vec<usize>> = HashMap::new();
+        for (row_idx, key) in other.column(right_col).into_iter().enumerate() {
+            if let Some(key) = key {
+                right_by_key.entry(key).or_default().push(row_idx);
+            }
+        }
+        let left_cells = |row: &[Cell]| -> Vec<Cell> {
+            (0..left_width)
+                .map(|col| row.get(col).cloned().unwrap_or(Cell { value: None }))
+                .collect()
+        };
+        let right_cells = |row: &[Cell]| -> Vec<Cell> {
+            (0..right_width)
+                .filter(|col| *col != right_col)
+                .map(|col| row.get(col).cloned().unwrap_or(Cell { value: None }))
+                .collect()
+        };
+
+        let mut data = vec![];
+        let mut right_matched = vec![false; other.data.len()];
+        for left_row in &self.data {
+            let key = left_row.get(left_col).and_then(|cell| cell.value.as_ref());
+            match key.and_then(|key| right_by_key.get(key)) {
+                Some(matches) => {
+                    for &right_idx in matches {
+                        right_matched[right_idx] = true;
+                        let mut row = left_cells(left_row);
+                        row.extend(right_cells(&other.data[right_idx]));
+                        data.push(row);
+                    }
+                }
+                None if keep_left => {
+                    let mut row = left_cells(left_row);
+                    row.resize_with(left_width + right_width.saturating_sub(1), || Cell { value: None });
+                    data.push(row);
+                }
+                None => {}
+            }
+        }
+        if keep_right {
+            for (right_row, _) in other.data
+                .iter()
+                .zip(&right_matched)
+                .filter(|(_, matched)| !**matched) {
+                let mut row: Vec<Cell> = (0..left_width).map(|_| Cell { value: None }).collect();
+                if let Some(cell) = row.get_mut(left_col) {
+                    cell.value = right_row.get(right_col).and_then(|cell| cell.value.clone());
+                }
+                row.extend(right_cells(right_row));
+                data.push(row);
+            }
+        }
+        DataFrame { headers, data }
+    }
+
+    /// Reads any supported file into the frame, picking the reader from the
+    /// extension: `.xlsx`/`.xlsm`, `.xls`, `.ods`, `.csv` or gzipped `.csv.gz`.
+    /// Sheet, header row and the rest come from `opts`.
+    /// Other extensions give `DataFrameError::UnsupportedFormat`.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read(&mut self, path: &str, opts: ReadOptions) -> Result<ReadReport> {
+        let lower = path.to_lowercase();
+        let has_extension = |extensions: &[&str]| extensions.iter().any(|ext| lower.ends_with(ext));
+        if has_extension(&[".xlsx", ".xlsm"]) {
+            self.read_from_xlsx_with(path, &opts)
+        } else if has_extension(&[".xls"]) {
+            self.read_from_xls_with(path, &opts)
+        } else if has_extension(&[".ods"]) {
+            self.read_from_ods_with(path, &opts)
+        } else if has_extension(&[".csv", ".csv.gz"]) {
+            self.read_from_csv_with(path, &opts)
+        } else {
+            Err(DataFrameError::UnsupportedFormat(path.to_string()))
+        }
+    }
+
+    /// Reads a worksheet into the frame, replacing its contents, with default
+    /// options. `None` for the sheet reads "Sheet1" and `None` for the header
+    /// flag guesses via `detect_headers`. `read_from_xlsx_with` (or `read`)
+    /// takes a `ReadOptions` for everything else.
+    /// Returns `DataFrameError::EmptySheet` when the sheet exists but has no rows.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read_from_xlsx(
+        &mut self,
+        path: &str,
+        provided_sheet_name: Option<&str>,
+        provided_with_headers: Option<bool>
+    ) -> Result<()> {
+        let mut workbook: Xlsx<_> = open_workbook(path)?;
+        self.read_workbook(&mut workbook, provided_sheet_name, provided_with_headers, &ReadOptions::default())?;
+        Ok(())
+    }
+
+    /// Same as `read_from_xlsx`, with sheet, header row and the rest taken from `options`.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read_from_xlsx_with(&mut self, path: &str, options: &ReadOptions) -> Result<ReadReport> {
+        let mut workbook: Xlsx<_> = open_workbook(path)?;
+        self.read_workbook(&mut workbook, None, None, options)
+    }
+
+    /// Reads a worksheet like `read_from_xlsx` but adds its rows below the
+    /// existing ones, e.g. to collect monthly files into one frame. An empty
+    /// frame just takes the sheet. Otherwise the widths must match and, when
+    /// both sides have headers, so must the names; columns in a different order
+    /// are lined up by name. On error the frame is unchanged.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn append_from_xlsx(
+        &mut self,
+        path: &str,
+        provided_sheet_name: Option<&str>,
+        provided_with_headers: Option<bool>
+    ) -> Result<()> {
+        let mut incoming = DataFrame::new(vec![]);
+        incoming.read_from_xlsx(path, provided_sheet_name, provided_with_headers)?;
+        if self.data.is_empty() && self.headers.is_none() {
+            *self = incoming;
+            return Ok(());
+        }
+        if incoming.width() != self.width() {
+            return Err(DataFrameError::LengthMismatch { expected: self.width(), found: incoming.width() });
+        }
+        if let (Some(ours), Some(theirs)) = (&self.headers, &incoming.headers) {
+            if let Some(name) = theirs.iter().find(|name| !ours.contains(name)) {
+                return Err(DataFrameError::ColumnNotFound(name.clone()));
+            }
+            if ours != theirs {
+                let order: Vec<&str> = ours.iter().map(String::as_str).collect();
+                incoming.reorder_columns(&order)?;
+            }
+        }
+        self.data.append(&mut incoming.data);
+        Ok(())
+    }
+
+    /// Same as `read_from_xlsx`, for legacy `.xls` (BIFF) workbooks.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read_from_xls(
+        &mut self,
+        path: &str,
+        provided_sheet_name: Option<&str>,
+        provided_with_headers: Option<bool>
+    ) -> Result<()> {
+        let mut workbook: Xls<_> = open_workbook(path)?;
+        self.read_workbook(&mut workbook, provided_sheet_name, provided_with_headers, &ReadOptions::default())?;
+        Ok(())
+    }
+
+    /// Same as `read_from_xlsx_with`, for legacy `.xls` (BIFF) workbooks.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read_from_xls_with(&mut self, path: &str, options: &ReadOptions) -> Result<ReadReport> {
+        let mut workbook: Xls<_> = open_workbook(path)?;
+        self.read_workbook(&mut workbook, None, None, options)
+    }
+
+    /// Same as `read_from_xlsx`, for OpenDocument (`.ods`) spreadsheets.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read_from_ods(
+        &mut self,
+        path: &str,
+        provided_sheet_name: Option<&str>,
+        provided_with_headers: Option<bool>
+    ) -> Result<()> {
+        let mut workbook: Ods<_> = open_workbook(path)?;
+        self.read_workbook(&mut workbook, provided_sheet_name, provided_with_headers, &ReadOptions::default())?;
+        Ok(())
+    }
+
+    /// Same as `read_from_xlsx_with`, for OpenDocument (`.ods`) spreadsheets.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read_from_ods_with(&mut self, path: &str, options: &ReadOptions) -> Result<ReadReport> {
+        let mut workbook: Ods<_> = open_workbook(path)?;
+        self.read_workbook(&mut workbook, None, None, options)
+    }
+
+    /// Reads a comma-separated file (gzipped when the path ends in `.gz`) into
+    /// the frame with default options; `None` for the header flag guesses.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read_from_csv(&mut self, path: &str, provided_with_headers: Option<bool>) -> Result<()> {
+        self.read_csv(path, provided_with_headers, &ReadOptions::default())?;
+        Ok(())
+    }
+
+    /// Reads a CSV file (gzipped when the path ends in `.gz`) into the frame.
+    /// Fields are split on `delimiter` and may be quoted with `"`. Integers and
+    /// decimals become numbers, everything else text; the other options work
+    /// like for workbooks. Columns in `column_types`, or every column when a
+    /// `number_format` is set, are converted from the field's text instead, so
+    /// a ZIP code forced to Text keeps its leading zeros.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn read_from_csv_with(&mut self, path: &str, options: &ReadOptions) -> Result<ReadReport> {
+        self.read_csv(path, None, options)
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn read_csv(&mut self, path: &str, provided_with_headers: Option<bool>, options: &ReadOptions) -> Result<ReadReport> {
+        let mut bytes = Vec::new();
+        if path.to_lowercase().ends_with(".gz") {
+            GzDecoder::new(File::open(path)?).read_to_end(&mut bytes)?;
+        } else {
+            File::open(path)?.read_to_end(&mut bytes)?;
+        }
+        let text = decode_text(bytes, options.encoding.as_deref())?;
+        // Excel writes a byte order mark that would otherwise end up in the first header
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        // Forced columns and locale formats parse the text themselves, so "00501" keeps its zeros
+        let keep_text = |col: usize| options.number_format.is_some() || options.column_types.iter().any(|(c, _)| *c == col);
+        let rows: Vec<Vec<Data>> = parse_csv(text, options.delimiter)
+            .into_iter()
+            .map(|row| row.into_iter().enumerate().map(|(col, field)| csv_field_value(field, keep_text(col))).collect())
+            .collect();
+        if rows.is_empty() {
+            self.clear();
+            return Err(DataFrameError::EmptySheet(path.to_string()));
+        }
+        self.load_rows(rows.iter().map(Vec::as_slice).collect(), path, provided_with_headers, options)
+    }
+
+    /// Same as `read_from_xlsx`, but reads the workbook from memory, e.g. a file
+    /// uploaded in the browser where there is no filesystem.
+    pub fn read_xlsx_bytes(
+        &mut self,
+        bytes: &[u8],
+        provided_sheet_name: Option<&str>,
+        provided_with_headers: Option<bool>
+    ) -> Result<()> {
+        let mut workbook = Xlsx::new(Cursor::new(bytes))?;
+        self.read_workbook(&mut workbook, provided_sheet_name, provided_with_headers, &ReadOptions::default())?;
+        Ok(())
+    }
+
+    /// Same as `read_xlsx_bytes`, with sheet, header row and the rest taken from `options`.
+    pub fn read_xlsx_bytes_with(&mut self, bytes: &[u8], options: &ReadOptions) -> Result<ReadReport> {
+        let mut workbook = Xlsx::new(Cursor::new(bytes))?;
+        self.read_workbook(&mut workbook, None, None, options)
+    }
+
+    /// Shared body of the readers, for any calamine workbook type. A provided
+    /// sheet or header flag wins over the one in `options`.
+    fn read_workbook<RS, R>(
+        &mut self,
+        workbook: &mut R,
+        provided_sheet_name: Option<&str>,
+        provided_with_headers: Option<bool>,
+        options: &ReadOptions
+    ) -> Result<ReadReport>
+        where RS: Read + Seek, R: Reader<RS> + MergedRegions, DataFrameError: From<R::Error>
+    {
+        let sheet_name = provided_sheet_name.or(options.sheet.as_deref()).unwrap_or("Sheet1");
+        let mut range = workbook.worksheet_range(sheet_name)?;
+        if options.fill_merged {
+            let regions = workbook.merged_regions_of(sheet_name)?;
+            fill_merged_regions(&mut range, &regions);
+        }
+
+        if range.is_empty() {
+            self.clear();
+            return Err(DataFrameError::EmptySheet(sheet_name.to_string()));
+        }
+        self.load_rows(range.rows().collect(), sheet_name, provided_with_headers, options)
+    }
+
+    /// Turns the raw rows of a sheet (or CSV file) into headers and cells.
+    /// `sheet_name` is only used in errors.
+    fn load_rows(
+        &mut self,
+        mut sheet_rows: Vec<&[Data]>,
+        sheet_name: &str,
+        provided_with_headers: Option<bool>,
+        options: &ReadOptions
+    ) -> Result<ReadReport> {
+        let mut headers: Option<Vec<String>> = None;
+        sheet_rows.drain(..options.skip_rows.min(sheet_rows.len()));
+        if options.trim_empty {
+            sheet_rows = trim_blank_edges(sheet_rows, options);
+            if sheet_rows.is_empty() {
+                self.clear();
+                return Err(DataFrameError::EmptySheet(sheet_name.to_string()));
+            }
+        }
+
+        // Size the buffers from the sheet dimensions up front to avoid regrowing them
+        let height = sheet_rows.len();
+        let capacity = options.max_rows.map_or(height, |max| max.min(height));
+        let mut data_for_dataframe: Vec<Vec<Cell>> = Vec::with_capacity(capacity);
+        // Without an explicit choice, guess from the top of the sheet
+        let with_headers = provided_with_headers.or(options.with_headers).unwrap_or_else(|| {
+            let sample: Vec<Vec<Cell>> = sheet_rows
+                .iter()
+                .take(HEADER_SAMPLE_ROWS)
+                .map(|row| row.iter().map(|cell| Cell { value: Self::convert_cell(cell, options) }).collect())
+                .collect();
+            DataFrame::new(sample).detect_headers()
+        });
+        let mut rows_iter = sheet_rows.into_iter();
+        if with_headers {
+            headers = rows_iter.next().map(|header_row| match &options.usecols {
+                Some(cols) => cols
+                    .iter()
+                    .map(|&col| header_row.get(col).map_or_else(|| format!("col_{}", col), |cell| cell.to_string()))
+                    .collect(),
+                None => header_row.iter().map(|cell| cell.to_string()).collect(),
+            });
+        }
+        let default_tokens = BoolTokens::default();
+        let bool_tokens = options.bool_tokens.as_ref().unwrap_or(&default_tokens);
+        // Cells per forced column type that couldn't be coerced
+        let mut coercion_failures: Vec<usize> = vec![0; options.column_types.len()];
+        for rows in rows_iter.take(options.max_rows.unwrap_or(usize::MAX)) {
+            let row_width = options.usecols.as_ref().map_or(rows.len(), Vec::len);
+            let mut temp_row: Vec<Cell> = Vec::with_capacity(row_width);
+            for idx in 0..row_width {
+                // Skipped columns are never converted
+                let col = options.usecols.as_ref().map_or(idx, |cols| cols[idx]);
+                let forced = options.column_types.iter().position(|(c, _)| *c == col);
+                let mut value = match rows.get(col) {
+                    // Text forced to Text skips `number_format`, so IDs like "00501" stay as written
+                    Some(cell @ Data::String(text)) if forced.is_some_and(|forced| options.column_types[forced].1 == DType::Text) => {
+                        (!is_blank(cell, options)).then(|| CellValues::from(text.as_str()))
+                    }
+                    cell => cell.and_then(|cell| Self::convert_cell(cell, options)),
+                };
+
+                if let Some(forced) = forced {
+                    if let Some(original) = value.take() {
+                        value = coerce_value(original, options.column_types[forced].1, bool_tokens);
+                        if value.is_none() {
+                            coercion_failures[forced] += 1;
+                        }
+                    }
+                }
+                temp_row.push(Cell { value });
+            }
+            // Check the contents of temp_row for debugging
+            data_for_dataframe.push(temp_row);
+        }
+        let report = ReadReport {
+            coercion_failures: coercion_failures
+                .into_iter()
+                .zip(&options.column_types)
+                .filter(|(failures, _)| *failures > 0)
+                .map(|(failures, (col, dtype))| (*col, *dtype, failures))
+                .collect(),
+        };
+        self.headers = headers;
+        self.data = data_for_dataframe;
+        self.check_header_width()?;
+        if options.dedupe_headers {
+            self.dedupe_headers();
+        }
+        if let Some(tokens) = &options.bool_tokens {
+            // Forced columns already have the type the caller asked for
+            let forced: Vec<usize> = options
+                .column_types
+                .iter()
+                .filter_map(|(col, _)| options.usecols.as_ref().map_or(Some(*col), |cols| cols.iter().position(|c| c == col)))
+                .collect();
+            self.infer_bools_except(tokens, &forced);
+        }
+        Ok(report)
+    }
+
+    /// Makes sure a consumed header row lines up with the data. A header row
+    /// shorter than the data is padded with `col_<index>` names; one wider than
+    /// the data is reported as `DataFrameError::HeaderWidthMismatch`.
+    fn check_header_width(&mut self) -> Result<()> {
+        let data_width = self.data.iter().map(Vec::len).max().unwrap_or(0);
+        let Some(headers) = self.headers.as_mut() else {
+            return Ok(());
+        };
+        if self.data.is_empty() || headers.len() == data_width {
+            return Ok(());
+        }
+        if headers.len() > data_width {
+            return Err(DataFrameError::HeaderWidthMismatch {
+                headers: headers.len(),
+                data: data_width,
+            });
+        }
+        for idx in headers.len()..data_width {
+            headers.push(format!("col_{}", idx));
+        }
+        Ok(())
+    }
+
+    /// Writes the frame to a single-sheet xlsx workbook.
+    /// Headers become the first row, numbers and text keep their Excel types,
+    /// dates are written as serials with a date format and nulls are left blank.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn write_to_xlsx(&self, path: &str, sheet_name: &str) -> Result<()> {
+        // Excel refuses to open workbooks whose sheet names break these rules
+        if sheet_name.is_empty()
+            || sheet_name.chars().count() > 31
+            || sheet_name.contains(['[', ']', ':', '*', '?', '/', '\\'])
+        {
+            return Err(DataFrameError::InvalidSheetName(sheet_name.to_string()));
+        }
+
+        let file = File::create(path)?;
+        let mut zip = ZipWriter::new(file);
+        let options = SimpleFileOptions::default();
+        let workbook_xml = XLSX_WORKBOOK.replace("{sheet_name}", &xml_escape(sheet_name));
+        let parts = [
+            ("[Content_Types].xml", XLSX_CONTENT_TYPES),
+            ("_rels/.rels", XLSX_ROOT_RELS),
+            ("xl/workbook.xml", workbook_xml.as_str()),
+            ("xl/_rels/workbook.xml.rels", XLSX_WORKBOOK_RELS),
+            ("xl/styles.xml", XLSX_STYLES),
+        ];
+        for (name, contents) in parts {
+            zip.start_file(name, options)?;
+            zip.write_all(contents.as_bytes())?;
+        }
+        zip.start_file("xl/worksheets/sheet1.xml", options)?;
+        zip.write_all(self.sheet_xml().as_bytes())?;
+        zip.finish()?;
+        Ok(())
+    }
+
+    /// Renders the frame as a GitHub-flavored markdown table, escaping pipes in
+    /// cells and turning line breaks into `<br>` so each row stays on one line.
+    pub fn to_markdown(&self) -> String {
+        let matrix: Vec<Vec<String>> = self
+            .display_matrix()
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|text| text.replace('|', "\\|").replace("\r\n", "<br>").replace(['\r', '\n'], "<br>"))
+                    .collect()
+            })
+            .collect();
+        // Markdown needs at least three dashes per separator cell
+        let widths: Vec<usize> = column_widths(&matrix)
+            .into_iter()
+            .map(|width| width.max(3))
+            .collect();
+
+        let mut markdown = String::new();
+        for (row_idx, row) in matrix.iter().enumerate() {
+            markdown.push('|');
+            for (text, width) in row.iter().zip(&widths) {
+                markdown.push_str(&format!(" {} |", pad(text, *width)));
+            }
+            markdown.push('\n');
+            if row_idx == 0 {
+                markdown.push('|');
+                for width in &widths {
+                    markdown.push_str(&format!(" {} |", "-".repeat(*width)));
+                }
+                markdown.push('\n');
+            }
+        }
+        markdown
+    }
+
+    /// Every cell rendered to its display string, nulls as `""`, optionally
+    /// behind a header row. Formatting matches the `Display` impl.
+    pub fn to_string_matrix(&self, include_headers: bool) -> Vec<Vec<String>> {
+        let mut matrix = self.display_matrix();
+        if !include_headers {
+            matrix.remove(0);
+        }
+        matrix
+    }
+
+    /// Every cell rendered with `Display`, nulls as `""`, behind a header row.
+    /// Headerless frames get the column indices as headers.
+    fn display_matrix(&self) -> Vec<Vec<String>> {
+        let width = self.width();
+        let mut matrix = Vec::with_capacity(self.data.len() + 1);
+        matrix.push(self.columns().map(|(name, _)| name.into_owned()).collect());
+        for row in &self.data {
+            matrix.push(
+                (0..width)
+                    .map(|col| match row.get(col).and_then(|cell| cell.value.as_ref()) {
+                        Some(value) => value.to_string(),
+                        None => String::new(),
+                    })
+                    .collect()
+            );
+        }
+        matrix
+    }
+
+    /// Renders the frame as an HTML `<table>`, escaping cell text.
+    /// Values use their `Display` form and nulls become empty cells.
+    pub fn to_html(&self) -> String {
+        let width = self.width();
+        let mut html = String::from("<table>\n");
+        if let Some(headers) = &self.headers {
+            html.push_str("  <thead>\n    <tr>");
+            for header in headers {
+                html.push_str(&format!("<th>{}</th>", xml_escape(header)));
+            }
+            html.push_str("</tr>\n  </thead>\n");
+        }
+        html.push_str("  <tbody>\n");
+        for row in &self.data {
+            html.push_str("    <tr>");
+            for col in 0..width {
+                match row.get(col).and_then(|cell| cell.value.as_ref()) {
+                    Some(value) => html.push_str(&format!("<td>{}</td>", xml_escape(&value.to_string()))),
+                    None => html.push_str("<td></td>"),
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("  </tbody>\n</table>\n");
+        html
+    }
+
+    /// Writes the frame as comma-separated values, headers first when present.
+    /// `float_precision` fixes the number of decimals; `None` writes the shortest
+    /// form that reads back exactly, so `Float(3.0)` becomes `3`. Non-finite
+    /// floats are left empty like nulls, as in `to_json` and `write_to_xlsx`.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn write_to_csv(&self, path: &str, float_precision: Option<usize>) -> Result<()> {
+        let mut file = std::io::BufWriter::new(File::create(path)?);
+        if let Some(headers) = &self.headers {
+            let line: Vec<String> = headers.iter().map(|header| csv_escape(header, ',')).collect();
+            writeln!(file, "{}", line.join(","))?;
+        }
+        let width = self.width();
+        for row in &self.data {
+            let line: Vec<String> = (0..width)
+                .map(|col| match row.get(col).and_then(|cell| cell.value.as_ref()) {
+                    Some(CellValues::Float(val)) if val.is_finite() => format_float(*val, float_precision),
+                    Some(CellValues::Float(_)) | None => String::new(),
+                    Some(value) => csv_escape(&value.to_string(), ','),
+                })
+                .collect();
+            writeln!(file, "{}", line.join(","))?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Renders the frame as a JSON array with one object per row, keyed by
+    /// column name. Dates are `"YYYY-MM-DD"` strings, nulls and non-finite
+    /// floats are `null`. `float_precision` works as in `write_to_csv`.
+    pub fn to_json(&self, float_precision: Option<usize>) -> String {
+        let names: Vec<String> = self
+            .columns()
+            .map(|(name, _)| json_string(&name))
+            .collect();
+        let mut json = String::from("[");
+        for (row_idx, row) in self.data.iter().enumerate() {
+            if row_idx > 0 {
+                json.push(',');
+            }
+            json.push('{');
+            for (col, name) in names.iter().enumerate() {
+                if col > 0 {
+                    json.push(',');
+                }
+                let value = match row.get(col).and_then(|cell| cell.value.as_ref()) {
+                    Some(CellValues::Int(val)) => val.to_string(),
+                    Some(CellValues::Int64(val)) => val.to_string(),
+                    Some(CellValues::Float(val)) if val.is_finite() => format_float(*val, float_precision),
+                    Some(CellValues::Float(_)) | None => "null".to_string(),
+                    Some(CellValues::Bool(val)) => val.to_string(),
+                    Some(other) => json_string(&other.to_string()),
+                };
+                json.push_str(&format!("{}:{}", name, value));
+            }
+            json.push('}');
+        }
+        json.push(']');
+        json
+    }
+
+    /// Builds the worksheet XML used by `write_to_xlsx`.
+    fn sheet_xml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>"
+        );
+        let mut row_number = 0;
+        if let Some(headers) = &self.headers {
+            row_number += 1;
+            xml.push_str(&format!("<row r=\"{}\">", row_number));
+            for (col, header) in headers.iter().enumerate() {
+                xml.push_str(&xlsx_text_cell(&cell_reference(row_number, col), header));
+            }
+            xml.push_str("</row>");
+        }
+        for row in &self.data {
+            row_number += 1;
+            xml.push_str(&format!("<row r=\"{}\">", row_number));
+            for (col, cell) in row.iter().enumerate() {
+                let reference = cell_reference(row_number, col);
+                match &cell.value {
+                    Some(CellValues::Int(val)) => {
+                        xml.push_str(&format!("<c r=\"{}\"><v>{}</v></c>", reference, val));
+                    }
+                    Some(CellValues::Int64(val)) => {
+                        xml.push_str(&format!("<c r=\"{}\"><v>{}</v></c>", reference, val));
+                    }
+                    Some(CellValues::Float(val)) if val.is_finite() => {
+                        xml.push_str(&format!("<c r=\"{}\"><v>{}</v></c>", reference, val));
+                    }
+                    #[cfg(feature = "decimal")]
+                    Some(CellValues::Decimal(val)) => {
+                        xml.push_str(&format!("<c r=\"{}\"><v>{}</v></c>", reference, val));
+                    }
+                    Some(CellValues::Bool(val)) => {
+                        xml.push_str(&format!("<c r=\"{}\" t=\"b\"><v>{}</v></c>", reference, u8::from(*val)));
+                    }
+                    Some(CellValues::Text(val)) => {
+                        xml.push_str(&xlsx_text_cell(&reference, val));
+                    }
+                    Some(CellValues::Date(date)) =>
+                        match date.to_excel_serial() {
+                            // Style 1 in XLSX_STYLES is the yyyy-mm-dd date format
+                            Ok(serial) => {
+                                xml.push_str(
+                                    &format!("<c r=\"{}\" s=\"1\"><v>{}</v></c>", reference, serial)
+                                );
+                            }
+                            Err(_) => {
+                                xml.push_str(&xlsx_text_cell(&reference, &date.to_string()));
+                            }
+                        }
+                    Some(CellValues::Error(err)) => {
+                        xml.push_str(
+                            &format!("<c r=\"{}\" t=\"e\"><v>{}</v></c>", reference, xml_escape(err))
+                        );
+                    }
+                    // Nulls and non-finite floats are left blank
+                    _ => {}
+                }
+            }
+            xml.push_str("</row>");
+        }
+        xml.push_str("</sheetData></worksheet>");
+        xml
+    }
+}
+
+impl<'a> ColumnView<'a> {
+    /// Number of rows in the view, nulls included.
+    pub fn len(&self) -> usize {
+        self.frame.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frame.data.is_empty()
+    }
+
+    /// Value at `row`; `None` for nulls, non-numeric cells and out-of-range rows.
+    pub fn get(&self, row: usize) -> Option<f64> {
+        self.frame.data.get(row)?.get(self.col)?.value.as_ref()?.as_f64()
+    }
+
+    /// Values top to bottom, with `None` like in `column_as_f64`.
+    pub fn iter(&self) -> impl Iterator<Item = Option<f64>> + 'a {
+        let col = self.col;
+        self.frame
+            .data
+            .iter()
+            .map(move |row| row.get(col).and_then(|cell| cell.value.as_ref()).and_then(CellValues::as_f64))
+    }
+}
+
+impl Date {
+    /// Parses date components based on the given format.
+    fn is_leap_year(year: u32) -> bool {
+        (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+    }
+    fn days_in_year(year: u16) -> u32 {
+        if Self::is_leap_year(year.into()) { 366 } else { 365 }
+    }
+    fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 => if Self::is_leap_year(year.into()) { 29 } else { 28 }
+            _ => 0,
+        }
+    }
+    pub fn from_excel_datetype(serial: u32) -> Result<Self, DateParseError> {
+        if !(1..=MAX_EXCEL_SERIAL).contains(&serial) {
+            return Err(DateParseError::InvalidSerialNumber);
+        }
+
+        let base_year = 1900;
+        let corrected_serial = if serial > 60 { serial - 1 } else { serial };
+        let days_since_base = corrected_serial - 1;
+
+        let mut year = base_year;
+        let mut days_remaining: u32 = days_since_base;
+
+        while days_remaining >= Self::days_in_year(year) {
+            days_remaining -= Self::days_in_year(year);
+            year += 1;
+        }
+
+        let mut month = 1;
+        while days_remaining >= Self::days_in_month(year, month).into() {
+            days_remaining -= Self::days_in_month(year, month) as u32;
+            month += 1;
+        }
+
+        let day = (days_remaining as u8) + 1;
+        if day > Self::days_in_month(year, month) {
+            return Err(DateParseError::InvalidDate);
+        }
+
+        Ok(Date { year: year.into(), month, day })
+    }
+    /// Converts the date back into an Excel (1900 date system) serial number.
+    /// Mirrors `from_excel_datetype`, including Excel's phantom 29 February 1900.
+    pub fn to_excel_serial(self) -> Result<u32, DateParseError> {
+        // Excel has no serials outside these years
+        if !(1900..=9999).contains(&self.year) {
+            return Err(DateParseError::InvalidDate);
+        }
+        let year = self.year as u16;
+        let mut days: u32 = (1900..year).map(Self::days_in_year).sum();
+        days += (1..self.month).map(|month| Self::days_in_month(year, month) as u32).sum::<u32>();
+        days += (self.day as u32) - 1;
+
+        let serial = days + 1;
+        Ok(if serial < 60 { serial } else { serial + 1 })
+    }
+
+    /// Formats the date with `YYYY`, `YY`, `MM` and `DD` tokens, e.g.
+    /// `"DD.MM.YYYY"` gives `"21.04.2024"`; any other character is copied as is.
+    /// A format without any token is `DateParseError::UnsupportedFormat`.
+    pub fn format(&self, fmt: &str) -> Result<String, DateParseError> {
+        let tokens = date_tokens(fmt)?;
+        let mut out = String::with_capacity(fmt.len());
+        for token in tokens {
+            match token {
+                DateToken::Year => out.push_str(&format!("{:04}", self.year)),
+                DateToken::ShortYear => out.push_str(&format!("{:02}", self.year % 100)),
+                DateToken::Month => out.push_str(&format!("{:02}", self.month)),
+                DateToken::Day => out.push_str(&format!("{:02}", self.day)),
+                DateToken::Literal(ch) => out.push(ch),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Parses text written in a `format`-style pattern, e.g. `"21.04.2024"` with
+    /// `"DD.MM.YYYY"`. Months and days may have one or two digits, `YY` means
+    /// 20YY, and a format without `DD` reads the first of the month.
+    pub fn from_str(text: &str, fmt: &str) -> Result<Self, DateParseError> {
+        let mut rest = text.trim();
+        let (mut year, mut month, mut day) = (None, None, Some(1));
+        for token in date_tokens(fmt)? {
+            let max_digits = match token {
+                DateToken::Literal(ch) => {
+                    rest = rest.strip_prefix(ch).ok_or(DateParseError::InvalidDate)?;
+                    continue;
+                }
+                DateToken::Year => 4,
+                _ => 2,
+            };
+            let digits = rest.bytes().take(max_digits).take_while(u8::is_ascii_digit).count();
+            if digits == 0 || (token == DateToken::ShortYear && digits != 2) {
+                return Err(DateParseError::InvalidDate);
+            }
+            let number: u32 = rest[..digits].parse().map_err(|_| DateParseError::InvalidDate)?;
+            rest = &rest[digits..];
+            match token {
+                DateToken::Year => year = Some(number),
+                DateToken::ShortYear => year = Some(2000 + number),
+                DateToken::Month => month = Some(number),
+                _ => day = Some(number),
+            }
+        }
+        if !rest.is_empty() {
+            return Err(DateParseError::InvalidDate);
+        }
+        let (year, month, day) = (
+            year.ok_or(DateParseError::InvalidYear)?,
+            month.ok_or(DateParseError::InvalidMonth)?,
+            day.ok_or(DateParseError::InvalidDay)?,
+        );
+        Self::from_numbers(year, month, day, "YYYY/MM/DD")
+    }
+
+    /// Days since 1970-01-01, the value Arrow and Parquet store for `date32`.
+    pub fn to_unix_days(self) -> i32 {
+        // Howard Hinnant's days_from_civil, with years starting in March
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        (era * 146_097 + day_of_era - 719_468) as i32
+    }
+
+    /// Inverse of `to_unix_days`.
+    pub fn from_unix_days(days: i32) -> Result<Self, DateParseError> {
+        let shifted = i64::from(days) + 719_468;
+        let era = shifted.div_euclid(146_097);
+        let day_of_era = shifted - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        if year < 1 {
+            return Err(DateParseError::InvalidYear);
+        }
+        Ok(Date { year: year as u32, month: month as u8, day: day as u8 })
+    }
+    pub fn from_numbers<T>(
+        frag1: T,
+        frag2: T,
+        frag3: T,
+        format: &str
+    ) -> Result<Self, DateParseError>
+        where T: Into<u32>
+    {
+        let months_with_31_days: [u8; 7] = [1, 3, 5, 7, 8, 10, 12];
+        let (year, month, day) = match format {
+            "YYYY/MM/DD" => (frag1.into(), frag2.into() as u8, frag3.into() as u8),
+            "DD/MM/YYYY" => (frag3.into(), frag2.into() as u8, frag1.into() as u8),
+            "MM/DD/YYYY" => (frag3.into(), frag1.into() as u8, frag2.into() as u8),
+            _ => {
+                return Err(DateParseError::UnsupportedFormat);
+            }
+        };
+
+        // validation logic
+        if day >= 32 {
+            return Err(DateParseError::InvalidDay);
+        }
+        if !months_with_31_days.contains(&month) && day > 31 {
+            return Err(DateParseError::InvalidDay);
+        }
+        if month == 2 {
+            if Self::is_leap_year(year) {
+                if day > 29 {
+                    return Err(DateParseError::InvalidDay);
+                }
+            } else {
+                if day > 28 {
+                    return Err(DateParseError::InvalidDay);
+                }
+            }
+        }
+
+        if !(1..=12).contains(&month) {
+            return Err(DateParseError::InvalidMonth);
+        }
+        if year == 0 {
+            return Err(DateParseError::InvalidYear);
+        }
+
+        Ok(Date { year, month, day })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        // Pad so there is at least one digit before the point, e.g. 0.05
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer_part, fraction_part) = digits.split_at(digits.len() - scale);
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        if fraction_part.is_empty() {
+            write!(f, "{}{}", sign, integer_part)
+        } else {
+            write!(f, "{}{}.{}", sign, integer_part, fraction_part)
+        }
+    }
+}
+
+impl fmt::Display for CellValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValues::Int(val) => write!(f, "{}", val),
+            CellValues::Int64(val) => write!(f, "{}", val),
+            CellValues::Float(val) => write!(f, "{}", val),
+            CellValues::Text(val) => write!(f, "{}", val),
+            CellValues::Date(date) => write!(f, "{}", date),
+            #[cfg(feature = "decimal")]
+            CellValues::Decimal(val) => write!(f, "{}", val),
+            CellValues::Bool(val) => write!(f, "{}", val),
+            CellValues::Error(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl fmt::Display for DataFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let matrix = self.display_matrix();
+        let widths = column_widths(&matrix);
+        for (row_idx, row) in matrix.iter().enumerate() {
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(text, width)| pad(text, *width))
+                .collect();
+            writeln!(f, "{}", line.join(" | ").trim_end())?;
+            if row_idx == 0 {
+                let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+                writeln!(f, "{}", rule.join("-+-"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DateParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateParseError::UnsupportedFormat => write!(f, "Unsupported date format"),
+            DateParseError::InvalidDay => write!(f, "Invalid day"),
+            DateParseError::InvalidMonth => write!(f, "Invalid month"),
+            DateParseError::InvalidYear => write!(f, "Invalid year"),
+            DateParseError::InvalidSerialNumber => write!(f, "Invalid Excel serial number"),
+            DateParseError::InvalidDate => write!(f, "Invalid date"),
+        }
+    }
+}
+
+impl fmt::Display for DataFrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataFrameError::ColumnNotFound(name) => write!(f, "Column not found: {}", name),
+            DataFrameError::DuplicateColumn(name) => write!(f, "Duplicate column: {}", name),
+            DataFrameError::MissingHeaders => write!(f, "DataFrame has no headers"),
+            DataFrameError::Io(err) => write!(f, "I/O error: {}", err),
+            DataFrameError::Calamine(err) => write!(f, "Workbook error: {}", err),
+            DataFrameError::DateParse(err) => write!(f, "Date parse error: {}", err),
+            DataFrameError::EmptySheet(name) => write!(f, "Sheet is empty: {}", name),
+            DataFrameError::InvalidSheetName(name) => write!(f, "Invalid sheet name: {}", name),
+            DataFrameError::ShapeMismatch(left, right) =>
+                write!(f, "Shape mismatch: {:?} vs {:?}", left, right),
+            DataFrameError::ColumnOutOfRange(idx) => write!(f, "Column index out of range: {}", idx),
+            DataFrameError::LengthMismatch { expected, found } =>
+                write!(f, "Length mismatch: expected {} values, found {}", expected, found),
+            DataFrameError::RowOutOfRange(idx) => write!(f, "Row index out of range: {}", idx),
+            DataFrameError::HeaderWidthMismatch { headers, data } =>
+                write!(f, "Header row has {} columns but the data has {}", headers, data),
+            DataFrameError::NoRows => write!(f, "DataFrame has no rows"),
+            DataFrameError::NoNumericValues(col) => write!(f, "Column {} has no numeric values", col),
+            DataFrameError::UnsupportedFormat(path) => write!(f, "Unsupported file format: {}", path),
+            DataFrameError::Parquet(msg) => write!(f, "Parquet error: {}", msg),
+            DataFrameError::NonNumeric { col, row } =>
+                write!(f, "Non-numeric value in column {} at row {}", col, row),
+            DataFrameError::NullValue { col, row } =>
+                write!(f, "Null value in column {} at row {}", col, row),
+            DataFrameError::UnsupportedEncoding(label) => write!(f, "Unsupported text encoding: {}", label),
+            DataFrameError::UnsortedBins => write!(f, "Bin edges must be strictly increasing"),
+        }
+    }
+}
+
+impl std::error::Error for DateParseError {}
+
+impl std::error::Error for DataFrameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DataFrameError::Io(err) => Some(err),
+            DataFrameError::Calamine(err) => Some(err),
+            DataFrameError::DateParse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DataFrameError {
+    fn from(err: std::io::Error) -> Self {
+        DataFrameError::Io(err)
+    }
+}
+
+impl From<calamine::Error> for DataFrameError {
+    fn from(err: calamine::Error) -> Self {
+        DataFrameError::Calamine(err)
+    }
+}
+
+// Lets `?` work directly on the per-format calamine errors
+impl From<calamine::XlsxError> for DataFrameError {
+    fn from(err: calamine::XlsxError) -> Self {
+        DataFrameError::Calamine(calamine::Error::Xlsx(err))
+    }
+}
+
+impl From<calamine::XlsError> for DataFrameError {
+    fn from(err: calamine::XlsError) -> Self {
+        DataFrameError::Calamine(calamine::Error::Xls(err))
+    }
+}
+
+impl From<calamine::OdsError> for DataFrameError {
+    fn from(err: calamine::OdsError) -> Self {
+        DataFrameError::Calamine(calamine::Error::Ods(err))
+    }
+}
+
+impl From<zip::result::ZipError> for DataFrameError {
+    fn from(err: zip::result::ZipError) -> Self {
+        DataFrameError::Io(err.into())
+    }
+}
+
+impl From<DateParseError> for DataFrameError {
+    fn from(err: DateParseError) -> Self {
+        DataFrameError::DateParse(err)
+    }
+}
+
+// Utils
+
+// Rows inspected by `read_from_xlsx` when it has to guess whether there is a header row
+const HEADER_SAMPLE_ROWS: usize = 100;
+
+// Days between the 1900 and 1904 date system epochs
+const EXCEL_1904_OFFSET: u32 = 1462;
+
+// Serial of 9999-12-31, the last date Excel can show
+const MAX_EXCEL_SERIAL: u32 = 2_958_465;
+
+// Key `partition_by` files rows with a null key under
+const NULL_PARTITION_KEY: &str = "<null>";
+
+// Added by the joins to right-hand column names the result already has
+const JOIN_RIGHT_SUFFIX: &str = "_right";
+
+// Static parts of the workbook written by `write_to_xlsx`
+const XLSX_CONTENT_TYPES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+<Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+<Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>\
+<Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>\
+</Types>";
+
+const XLSX_ROOT_RELS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>\
+</Relationships>";
+
+const XLSX_WORKBOOK: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+<sheets><sheet name=\"{sheet_name}\" sheetId=\"1\" r:id=\"rId1\"/></sheets>\
+</workbook>";
+
+const XLSX_WORKBOOK_RELS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/>\
+<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>\
+</Relationships>";
+
+const XLSX_STYLES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>
+<styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\
+<numFmts count=\"1\"><numFmt numFmtId=\"164\" formatCode=\"yyyy\\-mm\\-dd\"/></numFmts>\
+<fonts count=\"1\"><font><sz val=\"11\"/><name val=\"Calibri\"/></font></fonts>\
+<fills count=\"2\"><fill><patternFill patternType=\"none\"/></fill><fill><patternFill patternType=\"gray125\"/></fill></fills>\
+<borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders>\
+<cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs>\
+<cellXfs count=\"2\">\
+<xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/>\
+<xf numFmtId=\"164\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyNumberFormat=\"1\"/>\
+</cellXfs>\
+</styleSheet>";
+
+///format a float for export: fixed decimals, or the shortest exact form
+fn format_float(val: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(precision) => format!("{:.*}", precision, val),
+        None => val.to_string(),
+    }
+}
+
+///quote a CSV field when it contains the delimiter, quotes, line breaks or edge spaces
+fn csv_escape(field: &str, delimiter: char) -> String {
+    let needs_quotes = field.contains([delimiter, '"', '\n', '\r'])
+        || field.starts_with(' ')
+        || field.ends_with(' ');
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+///quoted and escaped JSON string literal
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+    json
+}
+
+///widest cell per column of a rendered table, counted in characters
+fn column_widths(matrix: &[Vec<String>]) -> Vec<usize> {
+    let mut widths: Vec<usize> = vec![];
+    for row in matrix {
+        for (col, text) in row.iter().enumerate() {
+            let len = text.chars().count();
+            match widths.get_mut(col) {
+                Some(width) => {
+                    *width = (*width).max(len);
+                }
+                None => widths.push(len),
+            }
+        }
+    }
+    widths
+}
+
+///move values down by `periods` (up when negative), filling the gap with `None`
+fn shift_values<T: Clone>(values: &[Option<T>], periods: i64) -> Vec<Option<T>> {
+    let len = values.len();
+    let offset = usize::try_from(periods.unsigned_abs()).unwrap_or(usize::MAX).min(len);
+    let mut shifted = Vec::with_capacity(len);
+    if periods >= 0 {
+        shifted.resize(offset, None);
+        shifted.extend_from_slice(&values[..len - offset]);
+    } else {
+        shifted.extend_from_slice(&values[offset..]);
+        shifted.resize(len, None);
+    }
+    shifted
+}
+
+///pad text with zeros after any leading sign up to `width` characters
+fn zero_pad(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len >= width {
+        return text.to_string();
+    }
+    let (sign, digits) = match text.strip_prefix(['+', '-']) {
+        Some(rest) => (&text[..1], rest),
+        None => ("", text),
+    };
+    format!("{}{}{}", sign, "0".repeat(width - len), digits)
+}
+
+///left-align text in a column of the given character width
+fn pad(text: &str, width: usize) -> String {
+    format!("{:<width$}", text, width = width)
+}
+
+///escape text for use inside XML (and HTML) content and attributes
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+///A1-style reference for a 1-based row and 0-based column, e.g. (1, 27) -> "AB1"
+fn cell_reference(row_number: usize, col: usize) -> String {
+    let mut letters = Vec::new();
+    let mut remaining = col + 1;
+    while remaining > 0 {
+        let rem = (remaining - 1) % 26;
+        letters.push((b'A' + (rem as u8)) as char);
+        remaining = (remaining - 1) / 26;
+    }
+    letters.iter().rev().collect::<String>() + &row_number.to_string()
+}
+
+///inline string cell, so the writer doesn't need a shared strings table
+fn xlsx_text_cell(reference: &str, text: &str) -> String {
+    format!(
+        "<c r=\"{}\" t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+        reference,
+        xml_escape(text)
+    )
+}
+
+///assert what is the type of the cell in worksheet
+///text matching one of `options.null_values` is read as a null
+fn into_cell_value(data: &dyn DataType, options: &ReadOptions) -> Option<CellValues> {
+    if let Some(val) = data.get_int() {
+        // Values outside the i32 range become Int64 rather than being dropped
+        return Some(CellValues::from(val));
+    } else if let Some(val) = data.get_float() {
+        return Some(CellValues::Float(val));
+    } else if let Some(val) = data.get_string() {
+        if options.null_values.iter().any(|null_value| null_value == val) {
+            return None;
+        }
+        if let Some(format) = &options.number_format {
+            return Some(type_inference(val, format));
+        }
+        return Some(CellValues::Text(val.into()));
+    } else if let Some(val) = data.get_bool() {
+        return Some(CellValues::Text(val.to_string().into()));
+    } else if data.is_empty() {
+        return None;
+    } else if let Some(err) = data.get_error() {
+        if options.keep_errors {
+            return Some(CellValues::Error(err.to_string()));
+        }
+        return None;
+    }
+
+    None
+}
+
+///turn raw file bytes into text: strict UTF-8 without a label, otherwise the
+///labelled encoding, e.g. "latin1" or "windows-1252"
+fn decode_text(bytes: Vec<u8>, encoding: Option<&str>) -> Result<String> {
+    match encoding {
+        None => String::from_utf8(bytes).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err).into()),
+        #[cfg(feature = "encoding_rs")]
+        Some(label) => {
+            let encoding = encoding_rs::Encoding::for_label(label.trim().as_bytes())
+                .ok_or_else(|| DataFrameError::UnsupportedEncoding(label.to_string()))?;
+            Ok(encoding.decode_without_bom_handling(&bytes).0.into_owned())
+        }
+        #[cfg(not(feature = "encoding_rs"))]
+        Some(label) if label.trim().eq_ignore_ascii_case("utf-8") || label.trim().eq_ignore_ascii_case("utf8") => {
+            decode_text(bytes, None)
+        }
+        #[cfg(not(feature = "encoding_rs"))]
+        Some(label) => Err(DataFrameError::UnsupportedEncoding(label.to_string())),
+    }
+}
+
+///split CSV text into rows of fields; quoted fields may hold delimiters,
+///doubled quotes and line breaks. Handles both \n and \r\n line endings
+fn parse_csv(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(ch),
+            }
+        } else if ch == '"' {
+            in_quotes = true;
+        } else if ch == delimiter {
+            row.push(std::mem::take(&mut field));
+        } else if ch == '\n' || ch == '\r' {
+            if ch == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            // Blank lines are skipped rather than read as a row with one empty field
+            if !row.is_empty() || !field.is_empty() {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+        } else {
+            field.push(ch);
+        }
+    }
+    // Last line without a trailing newline
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+///read a CSV field the way a worksheet would store it: numbers as numbers,
+///empty fields as empty cells and the rest as strings. With `keep_text` the
+///field stays a string even if it looks like a number
+fn csv_field_value(field: String, keep_text: bool) -> Data {
+    if field.is_empty() {
+        return Data::Empty;
+    }
+    // `f64` also parses words like "inf" and "NaN", which should stay text
+    if !keep_text && field.bytes().any(|byte| byte.is_ascii_digit()) {
+        if let Ok(val) = field.parse::<i64>() {
+            return Data::Int(val);
+        }
+        if let Ok(val) = field.parse::<f64>() {
+            return Data::Float(val);
+        }
+    }
+    Data::String(field)
+}
+
+///split a date format like "DD/MM/YYYY" into tokens; it needs at least one date token
+fn date_tokens(fmt: &str) -> Result<Vec<DateToken>, DateParseError> {
+    let mut tokens = Vec::new();
+    let mut rest = fmt;
+    while let Some(ch) = rest.chars().next() {
+        let (token, len) = if rest.starts_with("YYYY") {
+            (DateToken::Year, 4)
+        } else if rest.starts_with("YY") {
+            (DateToken::ShortYear, 2)
+        } else if rest.starts_with("MM") {
+            (DateToken::Month, 2)
+        } else if rest.starts_with("DD") {
+            (DateToken::Day, 2)
+        } else {
+            (DateToken::Literal(ch), ch.len_utf8())
+        };
+        tokens.push(token);
+        rest = &rest[len..];
+    }
+    if tokens.iter().all(|token| matches!(token, DateToken::Literal(_))) {
+        return Err(DateParseError::UnsupportedFormat);
+    }
+    Ok(tokens)
+}
+
+///copy the top-left value of each merged region into its other cells,
+///staying inside the sheet's used range
+fn fill_merged_regions(range: &mut Range<Data>, regions: &[Dimensions]) {
+    let (Some(start), Some(end)) = (range.start(), range.end()) else {
+        return;
+    };
+    for region in regions {
+        let value = match range.get_value(region.start) {
+            Some(value) if *value != Data::Empty => value.clone(),
+            _ => continue,
+        };
+        for row in region.start.0.max(start.0)..=region.end.0.min(end.0) {
+            for col in region.start.1.max(start.1)..=region.end.1.min(end.1) {
+                if (row, col) != region.start {
+                    range.set_value((row, col), value.clone());
+                }
+            }
+        }
+    }
+}
+
+///empty cells and null sentinels, which `trim_empty` strips from the edges
+fn is_blank(data: &Data, options: &ReadOptions) -> bool {
+    match data.get_string() {
+        Some(val) => options.null_values.iter().any(|null_value| null_value == val),
+        None => data.is_empty(),
+    }
+}
+
+///drop the blank leading/trailing rows and columns of a sheet
+fn trim_blank_edges<'a>(rows: Vec<&'a [Data]>, options: &ReadOptions) -> Vec<&'a [Data]> {
+    let filled_row = |row: &&[Data]| row.iter().any(|cell| !is_blank(cell, options));
+    let (Some(top), Some(bottom)) = (rows.iter().position(filled_row), rows.iter().rposition(filled_row)) else {
+        return vec![];
+    };
+    let rows = &rows[top..=bottom];
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let filled_col = |col: &usize| {
+        rows.iter().any(|row| row.get(*col).is_some_and(|cell| !is_blank(cell, options)))
+    };
+    let left = (0..width).find(filled_col).unwrap_or(0);
+    let right = (0..width).rev().find(filled_col).unwrap_or(0);
+    rows.iter()
+        .map(|row| &row[left.min(row.len())..(right + 1).min(row.len())])
+        .collect()
+}
+
+///infer the value of a text cell, reading it as a number in `format` if it parses;
+///only used when `ReadOptions::number_format` is set
+///whole numbers become Int, so "1,234" with a ',' thousands separator is Int(1234)
+///scientific notation goes through `f64::from_str`, so "1.5e3" is Float(1500.0)
+fn type_inference(text: &str, format: &NumberFormat) -> CellValues {
+    let Some(number) = normalize_number(text, format) else {
+        return CellValues::Text(text.into());
+    };
+    if let Ok(val) = number.parse::<i64>() {
+        return CellValues::from(val);
+    }
+    match number.parse::<f64>() {
+        Ok(val) => CellValues::Float(val),
+        Err(_) => CellValues::Text(text.into()),
+    }
+}
+
+///rewrite a number in `format` into the form Rust parses, e.g. "1.234,56" -> "1234.56"
+///thousands groups must have three digits so that ordinary text isn't mangled
+fn normalize_number(text: &str, format: &NumberFormat) -> Option<String> {
+    let text = text.trim();
+    // Keeps words like "nan" or "inf" from parsing as floats
+    if !text.chars().any(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    // Only the mantissa has separators, e.g. "1,234.5E+10"
+    let (mantissa, exponent) = text.split_at(text.find(['e', 'E']).unwrap_or(text.len()));
+    let (integer_part, fraction_part) = match mantissa.split_once(format.decimal_separator) {
+        Some((integer_part, fraction_part)) => (integer_part, Some(fraction_part)),
+        None => (mantissa, None),
+    };
+
+    let mut normalized = String::with_capacity(text.len());
+    match format.thousands_separator {
+        Some(separator) if integer_part.contains(separator) => {
+            let mut groups = integer_part.split(separator);
+            let leading = groups.next()?;
+            let leading_digits = leading.trim_start_matches(['+', '-']);
+            if leading_digits.is_empty() || leading_digits.len() > 3 {
+                return None;
+            }
+            normalized.push_str(leading);
+            for group in groups {
+                if group.len() != 3 || !group.chars().all(|ch| ch.is_ascii_digit()) {
+                    return None;
+                }
+                normalized.push_str(group);
+            }
+        }
+        _ => normalized.push_str(integer_part),
+    }
+    if let Some(fraction_part) = fraction_part {
+        normalized.push('.');
+        normalized.push_str(fraction_part);
+    }
+    normalized.push_str(exponent);
+    Some(normalized)
+}
+
+///common type of a set of values, see `DataFrame::dtypes`
+fn infer_dtype<'a>(values: impl Iterator<Item = &'a CellValues>) -> Option<DType> {
+    let mut dtype: Option<DType> = None;
+    for value in values {
+        let value_dtype = match value {
+            CellValues::Int(_) | CellValues::Int64(_) => DType::Int,
+            CellValues::Float(_) => DType::Float,
+            CellValues::Text(_) => DType::Text,
+            CellValues::Date(_) => DType::Date,
+            #[cfg(feature = "decimal")]
+            CellValues::Decimal(_) => DType::Decimal,
+            CellValues::Bool(_) => DType::Bool,
+            CellValues::Error(_) => {
+                continue;
+            }
+        };
+        dtype = Some(match (dtype, value_dtype) {
+            (None, new) => new,
+            (Some(current), new) if current == new => current,
+            (Some(DType::Int), DType::Float) | (Some(DType::Float), DType::Int) => DType::Float,
+            #[cfg(feature = "decimal")]
+            (Some(DType::Int), DType::Decimal) | (Some(DType::Decimal), DType::Int) => DType::Decimal,
+            _ => DType::Text,
+        });
+    }
+    dtype
+}
+
+///ordering used for sorting: numbers by value, then dates, booleans, text and errors
+fn compare_values(a: &CellValues, b: &CellValues) -> Ordering {
+    fn rank(value: &CellValues) -> u8 {
+        match value {
+            CellValues::Int(_) | CellValues::Int64(_) | CellValues::Float(_) => 0,
+            #[cfg(feature = "decimal")]
+            CellValues::Decimal(_) => 0,
+            CellValues::Date(_) => 1,
+            CellValues::Bool(_) => 2,
+            CellValues::Text(_) => 3,
+            CellValues::Error(_) => 4,
+        }
+    }
+    if let (Some(x), Some(y)) = (integer_value(a), integer_value(b)) {
+        return x.cmp(&y);
+    }
+    #[cfg(feature = "decimal")]
+    if let (Some(x), Some(y)) = (exact_decimal(a), exact_decimal(b)) {
+        return x.cmp(&y);
+    }
+    if let (Some(x), Some(y)) = (a.as_f64(), b.as_f64()) {
+        return x.total_cmp(&y);
+    }
+    match (a, b) {
+        (CellValues::Date(x), CellValues::Date(y)) => x.cmp(y),
+        (CellValues::Bool(x), CellValues::Bool(y)) => x.cmp(y),
+        (CellValues::Text(x), CellValues::Text(y)) => x.cmp(y),
+        (CellValues::Error(x), CellValues::Error(y)) => x.cmp(y),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+///exact value of Decimal, Int and Int64 cells, so decimals compare without rounding
+#[cfg(feature = "decimal")]
+fn exact_decimal(value: &CellValues) -> Option<Decimal> {
+    match value {
+        CellValues::Decimal(val) => Some(*val),
+        _ => integer_value(value).map(|val| Decimal::new(val.into(), 0)),
+    }
+}
+
+///exact integer value of Int and Int64 cells, so large integers compare without rounding
+fn integer_value(value: &CellValues) -> Option<i64> {
+    match value {
+        CellValues::Int(val) => Some((*val).into()),
+        CellValues::Int64(val) => Some(*val),
+        _ => None,
+    }
+}
+
+///compare two cells of a sort column, nulls last whatever the direction
+fn compare_cells(a: Option<&CellValues>, b: Option<&CellValues>, ascending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if ascending => compare_values(a, b),
+        (Some(a), Some(b)) => compare_values(b, a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+///convert a value to the given type, `None` if it doesn't fit; `tokens` decide
+///which values are booleans
+fn coerce_value(value: CellValues, dtype: DType, tokens: &BoolTokens) -> Option<CellValues> {
+    match (dtype, value) {
+        // Errors are kept as they are, whatever the column type
+        (_, CellValues::Error(err)) => Some(CellValues::Error(err)),
+        (DType::Int, CellValues::Int(val)) => Some(CellValues::Int(val)),
+        (DType::Int, CellValues::Int64(val)) => Some(CellValues::Int64(val)),
+        (DType::Int, CellValues::Float(val)) => {
+            if val.fract() == 0.0 && val >= (i64::MIN as f64) && val < (i64::MAX as f64) {
+                Some(CellValues::from(val as i64))
+            } else {
+                None
+            }
+        }
+        (DType::Int, CellValues::Text(val)) => val.trim().parse::<i64>().ok().map(CellValues::from),
+        (DType::Float, CellValues::Int(val)) => Some(CellValues::Float(val.into())),
+        (DType::Float, CellValues::Int64(val)) => Some(CellValues::Float(val as f64)),
+        (DType::Float, CellValues::Float(val)) => Some(CellValues::Float(val)),
+        (DType::Float, CellValues::Text(val)) => val.trim().parse().ok().map(CellValues::Float),
+        (DType::Text, CellValues::Text(val)) => Some(CellValues::Text(val)),
+        (DType::Text, other) => Some(CellValues::Text(other.to_string().into())),
+        #[cfg(feature = "decimal")]
+        (DType::Int, CellValues::Decimal(val)) => {
+            let whole = val.normalized();
+            if whole.scale != 0 {
+                return None;
+            }
+            i64::try_from(whole.mantissa).ok().map(CellValues::from)
+        }
+        #[cfg(feature = "decimal")]
+        (DType::Float, CellValues::Decimal(val)) => Some(CellValues::Float(val.to_f64())),
+        #[cfg(feature = "decimal")]
+        (DType::Decimal, CellValues::Decimal(val)) => Some(CellValues::Decimal(val)),
+        #[cfg(feature = "decimal")]
+        (DType::Decimal, CellValues::Int(val)) => Some(CellValues::Decimal(Decimal::new(val.into(), 0))),
+        #[cfg(feature = "decimal")]
+        (DType::Decimal, CellValues::Int64(val)) => Some(CellValues::Decimal(Decimal::new(val.into(), 0))),
+        #[cfg(feature = "decimal")]
+        (DType::Decimal, CellValues::Float(val)) => Decimal::from_f64(val).map(CellValues::Decimal),
+        #[cfg(feature = "decimal")]
+        (DType::Decimal, CellValues::Text(val)) => Decimal::parse(&val).map(CellValues::Decimal),
+        (DType::Bool, CellValues::Bool(val)) => Some(CellValues::Bool(val)),
+        (DType::Bool, other) => tokens.parse(&other).map(CellValues::Bool),
+        (DType::Date, CellValues::Date(date)) => Some(CellValues::Date(date)),
+        // Serials past 9999-12-31, like large IDs, are rejected by `from_excel_datetype`
+        (DType::Date, CellValues::Int(serial)) if serial > 0 => {
+            Date::from_excel_datetype(serial as u32).ok().map(CellValues::Date)
+        }
+        (DType::Date, CellValues::Float(serial)) if serial >= 1.0 => {
+            Date::from_excel_datetype(serial as u32).ok().map(CellValues::Date)
+        }
+        _ => None,
+    }
+}
+
+// Parquet
+// Minimal Parquet support without pulling in the arrow stack: the writer emits a
+// single row group with one uncompressed, PLAIN-encoded page per column, every
+// column optional; the reader also takes dictionary encoding and gzip pages.
+// Enable with `--features parquet`.
+#[cfg(feature = "parquet")]
+mod parquet {
+    use super::*;
+    // Gzip pages are decoded from memory, so this is needed on wasm too
+    use flate2::read::GzDecoder;
+
+    const MAGIC: &[u8] = b"PAR1";
+    const CREATED_BY: &str = "DataFrameImpl";
+
+    // Physical types
+    const TYPE_BOOLEAN: i32 = 0;
+    const TYPE_INT32: i32 = 1;
+    const TYPE_INT64: i32 = 2;
+    const TYPE_DOUBLE: i32 = 5;
+    const TYPE_BYTE_ARRAY: i32 = 6;
+
+    // Converted (legacy logical) types and their LogicalType union field ids
+    const CONVERTED_UTF8: i32 = 0;
+    const CONVERTED_DATE: i32 = 6;
+    const LOGICAL_STRING: i16 = 1;
+    const LOGICAL_DATE: i16 = 6;
+
+    const REPETITION_OPTIONAL: i32 = 1;
+    const ENCODING_PLAIN: i32 = 0;
+    const ENCODING_RLE: i32 = 3;
+    const PAGE_DATA: i32 = 0;
+    const CODEC_UNCOMPRESSED: i32 = 0;
+
+    // Thrift compact protocol type ids
+    const COMPACT_I32: u8 = 5;
+    const COMPACT_I64: u8 = 6;
+    const COMPACT_BINARY: u8 = 8;
+    const COMPACT_LIST: u8 = 9;
+    const COMPACT_STRUCT: u8 = 12;
+
+    /// How one frame column is stored.
+    struct ColumnLayout {
+        physical_type: i32,
+        converted_type: Option<i32>,
+        logical_type: Option<i16>,
+    }
+
+    impl ColumnLayout {
+        fn for_dtype(dtype: Option<DType>) -> Self {
+            let (physical_type, converted_type, logical_type) = match dtype {
+                Some(DType::Int) => (TYPE_INT64, None, None),
+                Some(DType::Float) => (TYPE_DOUBLE, None, None),
+                Some(DType::Date) => (TYPE_INT32, Some(CONVERTED_DATE), Some(LOGICAL_DATE)),
+                Some(DType::Bool) => (TYPE_BOOLEAN, None, None),
+                // Text, decimals (which keep their exact digits) and all-null columns
+                _ => (TYPE_BYTE_ARRAY, Some(CONVERTED_UTF8), Some(LOGICAL_STRING)),
+            };
+            ColumnLayout { physical_type, converted_type, logical_type }
+        }
+
+        /// Appends the PLAIN bytes of a value; `false` when it doesn't fit the column type.
+        /// Booleans take one byte each here and are bit-packed by `pack_booleans`.
+        fn encode(&self, value: &CellValues, out: &mut Vec<u8>) -> bool {
+            match self.physical_type {
+                TYPE_BOOLEAN => match value {
+                    CellValues::Bool(val) => out.push(u8::from(*val)),
+                    _ => return false,
+                },
+                TYPE_INT64 => match integer_value(value) {
+                    Some(val) => out.extend_from_slice(&val.to_le_bytes()),
+                    None => return false,
+                },
+                TYPE_DOUBLE => match value.as_f64() {
+                    Some(val) => out.extend_from_slice(&val.to_le_bytes()),
+                    None => return false,
+                },
+                TYPE_INT32 => match value {
+                    CellValues::Date(date) => out.extend_from_slice(&date.to_unix_days().to_le_bytes()),
+                    _ => return false,
+                },
+                _ => {
+                    let text = value.to_string();
+                    out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+                    out.extend_from_slice(text.as_bytes());
+                }
+            }
+            true
+        }
+    }
+
+    /// Thrift compact protocol encoder, just enough for Parquet metadata.
+    #[derive(Default)]
+    struct CompactWriter {
+        buf: Vec<u8>,
+        last_field: i16,
+        outer_fields: Vec<i16>,
+    }
+
+    impl CompactWriter {
+        fn varint(&mut self, mut val: u64) {
+            while val >= 0x80 {
+                self.buf.push((val as u8) | 0x80);
+                val >>= 7;
+            }
+            self.buf.push(val as u8);
+        }
+
+        fn zigzag(&mut self, val: i64) {
+            self.varint(((val << 1) ^ (val >> 63)) as u64);
+        }
+
+        fn field(&mut self, id: i16, kind: u8) {
+            let delta = id - self.last_field;
+            if (1..=15).contains(&delta) {
+                self.buf.push(((delta as u8) << 4) | kind);
+            } else {
+                self.buf.push(kind);
+                self.zigzag(id.into());
+            }
+            self.last_field = id;
+        }
+
+        fn i32(&mut self, id: i16, val: i32) {
+            self.field(id, COMPACT_I32);
+            self.zigzag(val.into());
+        }
+
+        fn i64(&mut self, id: i16, val: i64) {
+            self.field(id, COMPACT_I64);
+            self.zigzag(val);
+        }
+
+        fn binary(&mut self, id: i16, bytes: &[u8]) {
+            self.field(id, COMPACT_BINARY);
+            self.varint(bytes.len() as u64);
+            self.buf.extend_from_slice(bytes);
+        }
+
+        fn list(&mut self, id: i16, element: u8, len: usize) {
+            self.field(id, COMPACT_LIST);
+            if len < 15 {
+                self.buf.push(((len as u8) << 4) | element);
+            } else {
+                self.buf.push(0xF0 | element);
+                self.varint(len as u64);
+            }
+        }
+
+        fn list_i32(&mut self, val: i32) {
+            self.zigzag(val.into());
+        }
+
+        fn list_binary(&mut self, bytes: &[u8]) {
+            self.varint(bytes.len() as u64);
+            self.buf.extend_from_slice(bytes);
+        }
+
+        /// Starts a struct; `None` for an element of a list, which has no field header.
+        fn begin_struct(&mut self, id: Option<i16>) {
+            if let Some(id) = id {
+                self.field(id, COMPACT_STRUCT);
+            }
+            self.outer_fields.push(self.last_field);
+            self.last_field = 0;
+        }
+
+        fn end_struct(&mut self) {
+            self.buf.push(0);
+            self.last_field = self.outer_fields.pop().unwrap_or(0);
+        }
+    }
+
+    ///PLAIN booleans: one bit per value, least significant bit first
+    fn pack_booleans(values: &[u8]) -> Vec<u8> {
+        let mut packed = vec![0u8; values.len().div_ceil(8)];
+        for (idx, value) in values.iter().enumerate() {
+            packed[idx / 8] |= value << (idx % 8);
+        }
+        packed
+    }
+
+    ///definition levels (1 = value present) in the RLE/bit-packed hybrid
+    ///encoding with bit width 1, prefixed by their byte length as in a v1 data page
+    fn encode_definition_levels(present: &[bool], out: &mut Vec<u8>) {
+        let mut runs = CompactWriter::default();
+        let mut idx = 0;
+        while idx < present.len() {
+            let run = present[idx..].iter().take_while(|&&flag| flag == present[idx]).count();
+            runs.varint((run as u64) << 1);
+            runs.buf.push(u8::from(present[idx]));
+            idx += run;
+        }
+        out.extend_from_slice(&(runs.buf.len() as u32).to_le_bytes());
+        out.extend_from_slice(&runs.buf);
+    }
+
+    impl DataFrame {
+        /// Writes the frame to a Parquet file. Int columns become INT64, Float
+        /// DOUBLE, Date the `date32` DATE type, Bool BOOLEAN and everything else
+        /// UTF-8 strings; column types come from `dtypes`. Cells that don't fit their column's
+        /// type (like a formula error in a number column) are written as nulls.
+        #[cfg(not(target_family = "wasm"))]
+        pub fn write_to_parquet(&self, path: &str) -> Result<()> {
+            let layouts: Vec<ColumnLayout> = self.dtypes().into_iter().map(ColumnLayout::for_dtype).collect();
+            let mut file = MAGIC.to_vec();
+            // (offset, page size) of each column chunk
+            let mut chunks: Vec<(usize, usize)> = Vec::with_capacity(layouts.len());
+            for (col, layout) in layouts.iter().enumerate() {
+                let mut values = Vec::new();
+                let present: Vec<bool> = self
+                    .column(col)
+                    .into_iter()
+                    .map(|value| value.is_some_and(|value| layout.encode(value, &mut values)))
+                    .collect();
+                if layout.physical_type == TYPE_BOOLEAN {
+                    values = pack_booleans(&values);
+                }
+                let mut page = Vec::new();
+                encode_definition_levels(&present, &mut page);
+                page.extend_from_slice(&values);
+
+                let mut header = CompactWriter::default();
+                header.i32(1, PAGE_DATA);
+                header.i32(2, page.len() as i32);
+                header.i32(3, page.len() as i32);
+                header.begin_struct(Some(5));
+                header.i32(1, present.len() as i32);
+                header.i32(2, ENCODING_PLAIN);
+                header.i32(3, ENCODING_RLE);
+                header.i32(4, ENCODING_RLE);
+                header.end_struct();
+                header.end_struct();
+
+                chunks.push((file.len(), header.buf.len() + page.len()));
+                file.extend_from_slice(&header.buf);
+                file.extend_from_slice(&page);
+            }
+
+            let names: Vec<String> = (0..layouts.len()).map(|col| self.column_name(col).into_owned()).collect();
+            let n_rows = self.data.len() as i64;
+            let mut meta = CompactWriter::default();
+            meta.i32(1, 1);
+            meta.list(2, COMPACT_STRUCT, layouts.len() + 1);
+            meta.begin_struct(None);
+            meta.binary(4, b"schema");
+            meta.i32(5, layouts.len() as i32);
+            meta.end_struct();
+            for (layout, name) in layouts.iter().zip(&names) {
+                meta.begin_struct(None);
+                meta.i32(1, layout.physical_type);
+                meta.i32(3, REPETITION_OPTIONAL);
+                meta.binary(4, name.as_bytes());
+                if let Some(converted) = layout.converted_type {
+                    meta.i32(6, converted);
+                }
+                if let Some(logical) = layout.logical_type {
+                    // LogicalType is a union of empty structs, e.g. StringType
+                    meta.begin_struct(Some(10));
+                    meta.begin_struct(Some(logical));
+                    meta.end_struct();
+                    meta.end_struct();
+                }
+                meta.end_struct();
+            }
+            meta.i64(3, n_rows);
+            meta.list(4, COMPACT_STRUCT, 1);
+            meta.begin_struct(None);
+            meta.list(1, COMPACT_STRUCT, layouts.len());
+            for ((layout, name), (offset, size)) in layouts.iter().zip(&names).zip(&chunks) {
+                meta.begin_struct(None);
+                meta.i64(2, *offset as i64);
+                meta.begin_struct(Some(3));
+                meta.i32(1, layout.physical_type);
+                meta.list(2, COMPACT_I32, 2);
+                meta.list_i32(ENCODING_PLAIN);
+                meta.list_i32(ENCODING_RLE);
+                meta.list(3, COMPACT_BINARY, 1);
+                meta.list_binary(name.as_bytes());
+                meta.i32(4, CODEC_UNCOMPRESSED);
+                meta.i64(5, n_rows);
+                meta.i64(6, *size as i64);
+                meta.i64(7, *size as i64);
+                meta.i64(9, *offset as i64);
+                meta.end_struct();
+                meta.end_struct();
+            }
+            meta.i64(2, chunks.iter().map(|(_, size)| *size as i64).sum());
+            meta.i64(3, n_rows);
+            meta.end_struct();
+            meta.binary(6, CREATED_BY.as_bytes());
+            meta.end_struct();
+
+            file.extend_from_slice(&meta.buf);
+            file.extend_from_slice(&(meta.buf.len() as u32).to_le_bytes());
+            file.extend_from_slice(MAGIC);
+            File::create(path)?.write_all(&file)?;
+            Ok(())
+        }
+    }
+
+    // Read-only constants: the reader accepts a wider set of files than the writer makes
+    const TYPE_FLOAT: i32 = 4;
+    const REPETITION_REPEATED: i32 = 2;
+    const ENCODING_PLAIN_DICTIONARY: i32 = 2;
+    const ENCODING_RLE_DICTIONARY: i32 = 8;
+    const PAGE_DICTIONARY: i32 = 2;
+    const PAGE_DATA_V2: i32 = 3;
+    const CODEC_GZIP: i32 = 2;
+
+    /// A decoded Thrift compact value; maps never appear in Parquet metadata.
+    #[derive(Debug)]
+    enum Thrift {
+        Bool(bool),
+        Int(i64),
+        Double(f64),
+        Binary(Vec<u8>),
+        List(Vec<Thrift>),
+        Struct(Vec<(i16, Thrift)>),
+    }
+
+    impl Thrift {
+        fn get(&self, id: i16) -> Option<&Thrift> {
+            match self {
+                Thrift::Struct(fields) => fields.iter().find(|(field, _)| *field == id).map(|(_, value)| value),
+                _ => None,
+            }
+        }
+
+        fn int(&self, id: i16) -> Option<i64> {
+            match self.get(id)? {
+                Thrift::Int(val) => Some(*val),
+                _ => None,
+            }
+        }
+
+        fn bool(&self, id: i16) -> Option<bool> {
+            match self.get(id)? {
+                Thrift::Bool(val) => Some(*val),
+                _ => None,
+            }
+        }
+
+        fn binary(&self, id: i16) -> Option<&[u8]> {
+            match self.get(id)? {
+                Thrift::Binary(bytes) => Some(bytes),
+                _ => None,
+            }
+        }
+
+        fn list(&self, id: i16) -> Option<&[Thrift]> {
+            match self.get(id)? {
+                Thrift::List(items) => Some(items),
+                _ => None,
+            }
+        }
+
+        /// Required integer field, as the Parquet spec types it.
+        fn required(&self, id: i16, what: &str) -> Result<i64> {
+            self.int(id).ok_or_else(|| malformed(&format!("missing {}", what)))
+        }
+    }
+
+    fn malformed(what: &str) -> DataFrameError {
+        DataFrameError::Parquet(what.to_string())
+    }
+
+    /// Bounds-checked cursor over a byte buffer.
+    struct ByteReader<'a> {
+        buf: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> ByteReader<'a> {
+        fn new(buf: &'a [u8]) -> Self {
+            ByteReader { buf, pos: 0 }
+        }
+
+        fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+            let end = self.pos.checked_add(len).filter(|end| *end <= self.buf.len());
+            let end = end.ok_or_else(|| malformed("unexpected end of data"))?;
+            let bytes = &self.buf[self.pos..end];
+            self.pos = end;
+            Ok(bytes)
+        }
+
+        fn remaining(&self) -> usize {
+            self.buf.len() - self.pos
+        }
+
+        fn byte(&mut self) -> Result<u8> {
+            Ok(self.take(1)?[0])
+        }
+
+        fn u32_le(&mut self) -> Result<u32> {
+            let bytes = self.take(4)?;
+            Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        }
+
+        fn varint(&mut self) -> Result<u64> {
+            let mut val = 0u64;
+            for shift in (0..64).step_by(7) {
+                let byte = self.byte()?;
+                val |= u64::from(byte & 0x7F) << shift;
+                if byte & 0x80 == 0 {
+                    return Ok(val);
+                }
+            }
+            Err(malformed("varint too long"))
+        }
+
+        fn zigzag(&mut self) -> Result<i64> {
+            let val = self.varint()?;
+            Ok(((val >> 1) as i64) ^ -((val & 1) as i64))
+        }
+
+        fn thrift_value(&mut self, kind: u8) -> Result<Thrift> {
+            Ok(match kind {
+                1 => Thrift::Bool(true),
+                2 => Thrift::Bool(false),
+                3 => Thrift::Int(i64::from(self.byte()? as i8)),
+                4..=6 => Thrift::Int(self.zigzag()?),
+                7 => {
+                    let bytes = self.take(8)?;
+                    Thrift::Double(f64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+                }
+                8 => {
+                    let len = self.varint()? as usize;
+                    Thrift::Binary(self.take(len)?.to_vec())
+                }
+                9 | 10 => {
+                    let header = self.byte()?;
+                    let element = header & 0x0F;
+                    let len = match header >> 4 {
+                        15 => self.varint()? as usize,
+                        short => short as usize,
+                    };
+                    let mut items = Vec::new();
+                    for _ in 0..len {
+                        // Booleans inside lists take a whole byte each
+                        items.push(if element == 1 || element == 2 {
+                            Thrift::Bool(self.byte()? == 1)
+                        } else {
+                            self.thrift_value(element)?
+                        });
+                    }
+                    Thrift::List(items)
+                }
+                12 => self.thrift_struct()?,
+                other => return Err(malformed(&format!("unsupported thrift type {}", other))),
+            })
+        }
+
+        fn thrift_struct(&mut self) -> Result<Thrift> {
+            let mut fields = Vec::new();
+            let mut last_field: i16 = 0;
+            loop {
+                let header = self.byte()?;
+                if header == 0 {
+                    return Ok(Thrift::Struct(fields));
+                }
+                let delta = (header >> 4) as i16;
+                let id = if delta == 0 { self.zigzag()? as i16 } else { last_field + delta };
+                last_field = id;
+                fields.push((id, self.thrift_value(header & 0x0F)?));
+            }
+        }
+    }
+
+    ///decode `count` values of the RLE/bit-packed hybrid encoding
+    fn decode_hybrid(reader: &mut ByteReader, bit_width: u32, count: usize) -> Result<Vec<u32>> {
+        if bit_width > 32 {
+            return Err(malformed("bit width over 32"));
+        }
+        let byte_width = bit_width.div_ceil(8) as usize;
+        // `count` comes from the file, so only reserve what the bytes could hold
+        let mut values = Vec::with_capacity(count.min(reader.remaining().saturating_mul(8)));
+        while values.len() < count {
+            let header = reader.varint()?;
+            if header & 1 == 0 {
+                let run = (header >> 1) as usize;
+                let mut val = 0u32;
+                for (idx, byte) in reader.take(byte_width)?.iter().enumerate() {
+                    val |= u32::from(*byte) << (8 * idx);
+                }
+                values.extend(std::iter::repeat_n(val, run.min(count - values.len())));
+            } else {
+                let groups = (header >> 1) as usize;
+                let len = groups.checked_mul(bit_width as usize).ok_or_else(|| malformed("bit-packed run too long"))?;
+                let bytes = reader.take(len)?;
+                let run = groups.checked_mul(8).ok_or_else(|| malformed("bit-packed run too long"))?;
+                for idx in 0..run.min(count - values.len()) {
+                    let mut val = 0u32;
+                    for bit in 0..bit_width as usize {
+                        let pos = idx * bit_width as usize + bit;
+                        val |= u32::from((bytes[pos / 8] >> (pos % 8)) & 1) << bit;
+                    }
+                    values.push(val);
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// Schema details of one leaf column needed to decode it.
+    struct ColumnSchema {
+        name: String,
+        physical_type: i32,
+        is_date: bool,
+        optional: bool,
+    }
+
+    impl ColumnSchema {
+        ///decode `count` PLAIN values
+        fn decode_plain(&self, reader: &mut ByteReader, count: usize) -> Result<Vec<CellValues>> {
+            if self.physical_type == TYPE_BOOLEAN {
+                let bytes = reader.take(count.div_ceil(8))?;
+                let mut values = Vec::with_capacity(count);
+                for idx in 0..count {
+                    let bit = (bytes[idx / 8] >> (idx % 8)) & 1;
+                    values.push(CellValues::Bool(bit == 1));
+                }
+                return Ok(values);
+            }
+            // Every other physical type takes at least one byte per value
+            let mut values = Vec::with_capacity(count.min(reader.remaining()));
+            for _ in 0..count {
+                values.push(match self.physical_type {
+                    TYPE_INT32 => {
+                        let val = i32::from_le_bytes(reader.take(4)?.try_into().expect("4 bytes"));
+                        if self.is_date {
+                            CellValues::Date(Date::from_unix_days(val)?)
+                        } else {
+                            CellValues::Int(val)
+                        }
+                    }
+                    TYPE_INT64 => CellValues::from(i64::from_le_bytes(reader.take(8)?.try_into().expect("8 bytes"))),
+                    TYPE_FLOAT => {
+                        let val = f32::from_le_bytes(reader.take(4)?.try_into().expect("4 bytes"));
+                        CellValues::Float(val.into())
+                    }
+                    TYPE_DOUBLE => CellValues::Float(f64::from_le_bytes(reader.take(8)?.try_into().expect("8 bytes"))),
+                    TYPE_BYTE_ARRAY => {
+                        let len = reader.u32_le()? as usize;
+                        CellValues::from(String::from_utf8_lossy(reader.take(len)?).into_owned())
+                    }
+                    other => return Err(malformed(&format!("unsupported physical type {} in column {}", other, self.name))),
+                });
+            }
+            Ok(values)
+        }
+
+        /// Reads every page of a column chunk.
+        fn read_chunk(&self, file: &[u8], meta: &Thrift) -> Result<Vec<Option<CellValues>>> {
+            let codec = meta.required(4, "codec")? as i32;
+            let total = meta.required(5, "num_values")? as usize;
+            let data_offset = meta.required(9, "data_page_offset")?;
+            // Some writers put 0 here when there is no dictionary page
+            let start = match meta.int(11) {
+                Some(dictionary_offset) if dictionary_offset > 0 && dictionary_offset < data_offset => dictionary_offset,
+                _ => data_offset,
+            } as usize;
+            let mut reader = ByteReader::new(file);
+            reader.take(start)?;
+            let mut dictionary: Vec<CellValues> = Vec::new();
+            let mut cells: Vec<Option<CellValues>> = Vec::with_capacity(total.min(file.len()));
+            while cells.len() < total {
+                let header = reader.thrift_struct()?;
+                let page_type = header.required(1, "page type")? as i32;
+                let uncompressed_size = header.required(2, "uncompressed page size")? as usize;
+                let compressed_size = header.required(3, "compressed page size")? as usize;
+                let page = reader.take(compressed_size)?;
+                let decompress = |bytes: &[u8]| -> Result<Vec<u8>> {
+                    match codec {
+                        CODEC_UNCOMPRESSED => Ok(bytes.to_vec()),
+                        CODEC_GZIP => {
+                            // Deflate cannot expand data more than 1032 times
+                            let mut out = Vec::with_capacity(uncompressed_size.min(bytes.len().saturating_mul(1032)));
+                            GzDecoder::new(bytes).read_to_end(&mut out)?;
+                            Ok(out)
+                        }
+                        other => Err(malformed(&format!("unsupported compression codec {}", other))),
+                    }
+                };
+
+                match page_type {
+                    PAGE_DICTIONARY => {
+                        let dict_header = header.get(7).ok_or_else(|| malformed("missing dictionary page header"))?;
+                        let count = dict_header.required(1, "dictionary size")? as usize;
+                        let body = decompress(page)?;
+                        dictionary = self.decode_plain(&mut ByteReader::new(&body), count)?;
+                    }
+                    PAGE_DATA | PAGE_DATA_V2 => {
+                        let (present, encoding, values) = if page_type == PAGE_DATA {
+                            let data_header = header.get(5).ok_or_else(|| malformed("missing data page header"))?;
+                            let count = data_header.required(1, "page num_values")? as usize;
+                            if count > total - cells.len() {
+                                return Err(malformed("page holds more values than its column chunk"));
+                            }
+                            let body = decompress(page)?;
+                            let mut body_reader = ByteReader::new(&body);
+                            let present = if self.optional {
+                                let len = body_reader.u32_le()? as usize;
+                                let mut levels = ByteReader::new(body_reader.take(len)?);
+                                decode_hybrid(&mut levels, 1, count)?
+                            } else {
+                                vec![1; count]
+                            };
+                            let rest = body[body_reader.pos..].to_vec();
+                            (present, data_header.required(2, "page encoding")?, rest)
+                        } else {
+                            let data_header = header.get(8).ok_or_else(|| malformed("missing data page v2 header"))?;
+                            let count = data_header.required(1, "page num_values")? as usize;
+                            if count > total - cells.len() {
+                                return Err(malformed("page holds more values than its column chunk"));
+                            }
+                            let levels_len = data_header.required(5, "definition levels length")? as usize;
+                            let repetition_len = data_header.required(6, "repetition levels length")? as usize;
+                            let mut page_reader = ByteReader::new(page);
+                            page_reader.take(repetition_len)?;
+                            let level_bytes = page_reader.take(levels_len)?;
+                            let present = if self.optional {
+                                decode_hybrid(&mut ByteReader::new(level_bytes), 1, count)?
+                            } else {
+                                vec![1; count]
+                            };
+                            let rest = &page[page_reader.pos..];
+                            let values = if data_header.bool(7).unwrap_or(true) { decompress(rest)? } else { rest.to_vec() };
+                            (present, data_header.required(4, "page encoding")?, values)
+                        };
+
+                        let n_present = present.iter().filter(|&&level| level == 1).count();
+                        let mut value_reader = ByteReader::new(&values);
+                        let decoded = match encoding as i32 {
+                            ENCODING_PLAIN => self.decode_plain(&mut value_reader, n_present)?,
+                            ENCODING_PLAIN_DICTIONARY | ENCODING_RLE_DICTIONARY => {
+                                let bit_width = u32::from(value_reader.byte()?);
+                                decode_hybrid(&mut value_reader, bit_width, n_present)?
+                                    .into_iter()
+                                    .map(|idx| {
+                                        dictionary.get(idx as usize).cloned().ok_or_else(|| malformed("dictionary index out of range"))
+                                    })
+                                    .collect::<Result<Vec<_>>>()?
+                            }
+                            other => return Err(malformed(&format!("unsupported encoding {} in column {}", other, self.name))),
+                        };
+                        let mut decoded = decoded.into_iter();
+                        cells.extend(present.iter().map(|&level| if level == 1 { decoded.next() } else { None }));
+                    }
+                    // Index pages and anything newer carry nothing the frame needs
+                    _ => {}
+                }
+            }
+            Ok(cells)
+        }
+    }
+
+    impl DataFrame {
+        /// Reads a Parquet file into the frame, replacing its contents. Headers
+        /// come from the schema. INT32/INT64 become Int, FLOAT/DOUBLE Float,
+        /// DATE Date, strings Text and BOOLEAN Bool.
+        /// Only flat schemas and uncompressed or gzip pages are supported; other
+        /// files give `DataFrameError::Parquet`.
+        #[cfg(not(target_family = "wasm"))]
+        pub fn read_from_parquet(&mut self, path: &str) -> Result<()> {
+            let file = std::fs::read(path)?;
+            if file.len() < 12 || !file.starts_with(MAGIC) || !file.ends_with(MAGIC) {
+                return Err(malformed("not a Parquet file"));
+            }
+            let footer_len = u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().expect("4 bytes")) as usize;
+            let footer_start = (file.len() - 8).checked_sub(footer_len).ok_or_else(|| malformed("footer too long"))?;
+            let meta = ByteReader::new(&file[footer_start..file.len() - 8]).thrift_struct()?;
+
+            let schema = meta.list(2).ok_or_else(|| malformed("missing schema"))?;
+            let mut columns = Vec::new();
+            for element in schema.iter().skip(1) {
+                let name = String::from_utf8_lossy(element.binary(4).unwrap_or_default()).into_owned();
+                let repetition = element.int(3).unwrap_or(0) as i32;
+                if element.int(5).is_some_and(|children| children > 0) || repetition == REPETITION_REPEATED {
+                    return Err(malformed(&format!("nested column {} is not supported", name)));
+                }
+                let is_date = element.int(6) == Some(CONVERTED_DATE.into())
+                    || element.get(10).and_then(|logical| logical.get(LOGICAL_DATE)).is_some();
+                columns.push(ColumnSchema {
+                    name,
+                    physical_type: element.required(1, "column type")? as i32,
+                    is_date,
+                    optional: repetition == REPETITION_OPTIONAL,
+                });
+            }
+
+            let mut column_cells: Vec<Vec<Option<CellValues>>> = vec![Vec::new(); columns.len()];
+            for row_group in meta.list(4).unwrap_or_default() {
+                let chunks = row_group.list(1).ok_or_else(|| malformed("row group without columns"))?;
+                for ((column, chunk), cells) in columns.iter().zip(chunks).zip(&mut column_cells) {
+                    let chunk_meta = chunk.get(3).ok_or_else(|| malformed("column chunk without metadata"))?;
+                    cells.extend(column.read_chunk(&file, chunk_meta)?);
+                }
+            }
+
+            let n_rows = column_cells.iter().map(Vec::len).max().unwrap_or(0);
+            let mut data: Vec<Vec<Cell>> = (0..n_rows).map(|_| Vec::with_capacity(columns.len())).collect();
+            for cells in column_cells {
+                let mut cells = cells.into_iter();
+                for row in &mut data {
+                    row.push(Cell { value: cells.next().flatten() });
+                }
+            }
+            self.headers = Some(columns.into_iter().map(|column| column.name).collect());
+            self.data = data;
+            Ok(())
+        }
+    }
+}
 
 // Main function for testing and debugging
 fn main() {
@@ -269,8 +4703,592 @@ fn main() {
         "Error when parsing"
     );
     println!("{:?}", date);
-    let mut dftest = DataFrame::new(vec![vec![]]);
-    println!("{:?}", dftest);
-    dftest.read_from_xlsx("data\\test.xlsx", Some("Sheet1"), Some(true));
-    println!("{:#?}", dftest)
+
+    // Reading and writing files needs a filesystem
+    #[cfg(not(target_family = "wasm"))]
+    {
+        let mut dftest = DataFrame::new(vec![vec![]]);
+        println!("{:?}", dftest);
+        dftest.read_from_xlsx("data/test.xlsx", Some("Sheet1"), Some(true)).expect("failed to read file");
+        println!("{:#?}", dftest);
+        println!("{}", dftest);
+        println!("{}", dftest.to_markdown());
+
+        // Writing and reading back gives the same frame, see `xlsx_round_trips_the_test_frame`
+        let roundtrip_path = std::env::temp_dir().join("dataframe_roundtrip.xlsx");
+        let roundtrip_path = roundtrip_path.to_str().expect("temp path is not UTF-8");
+        dftest.write_to_xlsx(roundtrip_path, "Sheet1").expect("failed to write file");
+        let mut roundtrip = DataFrame::new(vec![]);
+        roundtrip.read_from_xlsx(roundtrip_path, Some("Sheet1"), Some(true)).expect("failed to read file");
+        println!("{:#?}", roundtrip);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell as CountCell;
+
+    /// Counts allocations (reallocations included) per thread, so a test can
+    /// measure its own code while other tests run in parallel.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: CountCell<usize> = const { CountCell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            // `try_with` so thread teardown, when the counter is gone, still allocates
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations_during(run: impl FnOnce()) -> usize {
+        let before = ALLOCATIONS.with(CountCell::get);
+        run();
+        ALLOCATIONS.with(CountCell::get) - before
+    }
+
+    /// The frame every file-based test compares against.
+    fn test_frame() -> DataFrame {
+        let mut frame = DataFrame::new(vec![]);
+        frame.read_from_xlsx("data/test.xlsx", Some("Sheet1"), Some(true)).expect("failed to read file");
+        frame
+    }
+
+    /// Builds a frame from headers and rows of optional values.
+    fn frame(headers: &[&str], rows: Vec<Vec<Option<CellValues>>>) -> DataFrame {
+        let data = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|value| Cell { value }).collect())
+            .collect();
+        DataFrame { headers: Some(headers.iter().map(|name| name.to_string()).collect()), data }
+    }
+
+    fn int(val: i32) -> Option<CellValues> {
+        Some(CellValues::Int(val))
+    }
+
+    fn text(val: &str) -> Option<CellValues> {
+        Some(CellValues::from(val))
+    }
+
+    #[test]
+    fn empty_sheet_is_reported_apart_from_a_failed_read() {
+        let mut empty = DataFrame::new(vec![]);
+        let result = empty.read_from_xlsx("data/empty_sheet.xlsx", Some("Sheet1"), Some(true));
+        assert!(matches!(result, Err(DataFrameError::EmptySheet(ref name)) if name == "Sheet1"));
+    }
+
+    #[test]
+    fn formatted_numbers_are_inferred() {
+        let european = NumberFormat { decimal_separator: ',', thousands_separator: Some('.') };
+        assert_eq!(type_inference("1.234,56", &european), CellValues::Float(1234.56));
+        assert_eq!(type_inference("12.34.5", &european), CellValues::from("12.34.5"));
+        let formatted = NumberFormat { decimal_separator: '.', thousands_separator: Some(',') };
+        assert_eq!(type_inference("1,234", &formatted), CellValues::Int(1234));
+        assert_eq!(type_inference("red, green", &formatted), CellValues::from("red, green"));
+    }
+
+    #[test]
+    fn scientific_notation_is_read_as_float() {
+        let formatted = NumberFormat { decimal_separator: '.', thousands_separator: Some(',') };
+        let european = NumberFormat { decimal_separator: ',', thousands_separator: Some('.') };
+        assert_eq!(type_inference("1.5e3", &formatted), CellValues::Float(1500.0));
+        assert_eq!(type_inference("1,234.5E+3", &formatted), CellValues::Float(1_234_500.0));
+        assert_eq!(type_inference("1,5E-2", &european), CellValues::Float(0.015));
+        assert_eq!(type_inference("1e5", &formatted), CellValues::Float(100_000.0));
+        assert_eq!(type_inference("1E+10", &formatted), CellValues::Float(1e10));
+        assert_eq!(type_inference("12E3", &european), CellValues::Float(12_000.0));
+        assert_eq!(type_inference("1,234e3", &formatted), CellValues::Float(1_234_000.0));
+    }
+
+    #[test]
+    fn large_integers_are_kept() {
+        let large = into_cell_value(&Data::Int(3_000_000_000), &ReadOptions::default());
+        assert_eq!(large, Some(CellValues::Int64(3_000_000_000)));
+    }
+
+    #[test]
+    fn mean_tells_no_rows_from_no_numbers() {
+        let mut sparse = DataFrame::new(vec![]);
+        sparse.headers = Some(vec!["amount".to_string()]);
+        assert_eq!(sparse.mean(0), None);
+        assert!(matches!(sparse.mean_checked(0), Err(DataFrameError::NoRows)));
+        sparse.push_row(vec!["n/a".into()]).expect("row width matches");
+        assert_eq!(sparse.mean_or(0, 0.0), 0.0);
+        assert!(matches!(sparse.mean_checked(0), Err(DataFrameError::NoNumericValues(0))));
+    }
+
+    #[test]
+    fn bytes_read_like_the_file() {
+        let bytes = std::fs::read("data/test.xlsx").expect("failed to read file");
+        let mut from_bytes = DataFrame::new(vec![]);
+        from_bytes.read_xlsx_bytes(&bytes, Some("Sheet1"), Some(true)).expect("failed to read bytes");
+        assert_eq!(from_bytes.to_string(), test_frame().to_string());
+    }
+
+    #[test]
+    fn ods_reads_like_xlsx() {
+        let mut from_ods = DataFrame::new(vec![]);
+        from_ods.read_from_ods("data/test.ods", Some("Sheet1"), Some(true)).expect("failed to read file");
+        assert_eq!(format!("{:?}", from_ods), format!("{:?}", test_frame()));
+    }
+
+    #[test]
+    fn read_with_builder_matches_direct_read() {
+        let mut dispatched = DataFrame::new(vec![]);
+        dispatched
+            .read("data/test.xlsx", ReadOptions::default().sheet("Sheet1").with_headers(true))
+            .expect("failed to read file");
+        assert_eq!(format!("{:?}", dispatched), format!("{:?}", test_frame()));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_round_trips_a_typed_frame() {
+        let mut typed = DataFrame::new(vec![]);
+        typed.headers = Some(["id", "amount", "day", "name", "paid"].iter().map(|name| name.to_string()).collect());
+        let day = Date::from_numbers::<u32>(21, 4, 2024, "DD/MM/YYYY").expect("valid date");
+        let row = vec![1.into(), 2.5.into(), CellValues::Date(day), "żółw".into(), CellValues::Bool(true)];
+        typed.push_row(row).expect("row width matches");
+        let row = vec![3_000_000_000i64.into(), 0.5.into(), CellValues::Date(day), "b".into(), CellValues::Bool(false)];
+        typed.push_row(row).expect("row width matches");
+        typed.push_row(vec![2.into(), 1.0.into(), CellValues::Date(day), "c".into(), CellValues::Bool(true)]).expect("row width matches");
+        typed.data[1][3].value = None;
+        typed.data[1][4].value = None;
+        let parquet_path = std::env::temp_dir().join("dataframe_roundtrip.parquet");
+        let parquet_path = parquet_path.to_str().expect("temp path is not UTF-8");
+        typed.write_to_parquet(parquet_path).expect("failed to write file");
+        let mut from_parquet = DataFrame::new(vec![]);
+        from_parquet.read_from_parquet(parquet_path).expect("failed to read file");
+        assert_eq!(format!("{:?}", from_parquet), format!("{:?}", typed));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_rejects_an_overflowing_bit_packed_run() {
+        let mut file = b"PAR1".to_vec();
+        // Data page header: DATA, 11 bytes, one RLE_DICTIONARY value
+        file.extend([0x15, 0x00, 0x15, 0x16, 0x15, 0x16, 0x2C, 0x15, 0x02, 0x15, 0x10, 0x00, 0x00]);
+        // Bit width 32, then a bit-packed run of 2^62 groups
+        file.extend([0x20, 0x81, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01]);
+        // Footer: one required INT32 column with one value at offset 4
+        let footer = [
+            0x15, 0x02, 0x19, 0x2C, 0x48, 0x01, b'r', 0x00, 0x15, 0x02, 0x38, 0x01, b'a', 0x00, 0x29, 0x1C, 0x19, 0x1C, 0x3C,
+            0x45, 0x00, 0x16, 0x02, 0x46, 0x08, 0x00, 0x00, 0x00, 0x00,
+        ];
+        file.extend(footer);
+        file.extend((footer.len() as u32).to_le_bytes());
+        file.extend(b"PAR1");
+        let parquet_path = std::env::temp_dir().join("dataframe_malformed.parquet");
+        std::fs::write(&parquet_path, file).expect("failed to write file");
+        let mut from_parquet = DataFrame::new(vec![]);
+        let result = from_parquet.read_from_parquet(parquet_path.to_str().expect("temp path is not UTF-8"));
+        assert!(matches!(result, Err(DataFrameError::Parquet(_))));
+    }
+
+    #[test]
+    fn xlsx_round_trips_the_test_frame() {
+        let dftest = test_frame();
+        let roundtrip_path = std::env::temp_dir().join("dataframe_roundtrip_test.xlsx");
+        let roundtrip_path = roundtrip_path.to_str().expect("temp path is not UTF-8");
+        dftest.write_to_xlsx(roundtrip_path, "Sheet1").expect("failed to write file");
+        let mut roundtrip = DataFrame::new(vec![]);
+        roundtrip.read_from_xlsx(roundtrip_path, Some("Sheet1"), Some(true)).expect("failed to read file");
+        assert_eq!(format!("{:?}", roundtrip), format!("{:?}", dftest));
+    }
+
+    #[test]
+    fn excel_serials_stop_at_year_9999() {
+        let last = Date { year: 9999, month: 12, day: 31 };
+        assert_eq!(last.to_excel_serial().ok(), Some(MAX_EXCEL_SERIAL));
+        assert!(Date { year: 70_000, month: 1, day: 1 }.to_excel_serial().is_err());
+        assert!(Date { year: 10_000, month: 1, day: 1 }.to_excel_serial().is_err());
+    }
+
+    #[test]
+    fn max_rows_reads_the_top_after_the_header() {
+        let dftest = test_frame();
+        let mut preview = DataFrame::new(vec![]);
+        preview.read("data/test.xlsx", ReadOptions::default().with_headers(true).max_rows(2)).expect("failed to read file");
+        assert_eq!(preview.headers, dftest.headers);
+        assert_eq!(preview.data.len(), 2.min(dftest.data.len()));
+        assert_eq!(format!("{:?}", preview.data[0]), format!("{:?}", dftest.data[0]));
+    }
+
+    #[test]
+    fn skip_rows_come_before_the_header() {
+        let mut skipped = DataFrame::new(vec![]);
+        skipped.read("data/test.xlsx", ReadOptions::default().with_headers(false).skip_rows(1)).expect("failed to read file");
+        assert_eq!(format!("{:?}", skipped.data), format!("{:?}", test_frame().data));
+    }
+
+    #[test]
+    fn usecols_keeps_the_requested_order() {
+        let dftest = test_frame();
+        let mut narrow = DataFrame::new(vec![]);
+        narrow.read("data/test.xlsx", ReadOptions::default().with_headers(true).usecols(&[1, 0])).expect("failed to read file");
+        let headers = dftest.headers.as_ref().expect("test frame has headers");
+        assert_eq!(narrow.headers, Some(vec![headers[1].clone(), headers[0].clone()]));
+        for (narrow_row, row) in narrow.data.iter().zip(&dftest.data) {
+            assert_eq!(format!("{:?}", narrow_row), format!("{:?}", [&row[1], &row[0]]));
+        }
+    }
+
+    #[test]
+    fn append_keeps_the_rows_already_read() {
+        let dftest = test_frame();
+        let mut combined = DataFrame::new(vec![]);
+        combined.append_from_xlsx("data/test.xlsx", Some("Sheet1"), Some(true)).expect("failed to read file");
+        combined.append_from_xlsx("data/test.xlsx", Some("Sheet1"), Some(true)).expect("failed to read file");
+        assert_eq!(combined.headers, dftest.headers);
+        assert_eq!(combined.data.len(), dftest.data.len() * 2);
+        let mut wrong_width = DataFrame::new(vec![]);
+        wrong_width.headers = Some(vec!["only".to_string()]);
+        let result = wrong_width.append_from_xlsx("data/test.xlsx", Some("Sheet1"), Some(true));
+        assert!(matches!(result, Err(DataFrameError::LengthMismatch { expected: 1, .. })));
+    }
+
+    #[test]
+    fn content_hash_follows_the_values() {
+        let dftest = test_frame();
+        let mut reread = test_frame();
+        assert_eq!(reread.content_hash(), dftest.content_hash());
+        reread.data.swap(0, 1);
+        assert_ne!(reread.content_hash(), dftest.content_hash());
+    }
+
+    #[test]
+    fn content_hash_is_pinned() {
+        let mixed = frame(&["id", "name"], vec![vec![int(1), text("a")], vec![None, Some(CellValues::Bool(true))]]);
+        assert_eq!(mixed.content_hash(), 6_425_142_057_664_695_470);
+    }
+
+    #[test]
+    fn align_to_reorders_drops_and_adds_columns() {
+        let dftest = test_frame();
+        let headers = dftest.headers.clone().expect("test frame has headers");
+        let aligned = dftest.align_to(&["missing", &headers[1], &headers[0]]).expect("template is valid");
+        assert_eq!(aligned.shape(), (dftest.data.len(), 3));
+        assert!(aligned.column(0).iter().all(Option::is_none));
+        assert_eq!(aligned.column(1), dftest.column(1));
+    }
+
+    #[test]
+    fn apply_columns_checks_lengths() {
+        let dftest = test_frame();
+        let numbered = dftest
+            .apply_columns(|col, values| values.iter().map(|_| CellValues::Int(col as i32)).collect())
+            .expect("lengths match");
+        assert_eq!(numbered.shape(), dftest.shape());
+        assert_eq!(numbered.column(1).first(), Some(&Some(&CellValues::Int(1))));
+        let result = dftest.apply_columns(|_, _| vec![]);
+        assert!(matches!(result, Err(DataFrameError::LengthMismatch { found: 0, .. })));
+    }
+
+    #[test]
+    fn chunks_cover_every_row_once() {
+        let dftest = test_frame();
+        let chunks = dftest.chunks(2);
+        assert_eq!(chunks.len(), dftest.data.len().div_ceil(2));
+        assert!(chunks.iter().all(|chunk| chunk.headers == dftest.headers && chunk.data.len() <= 2));
+        assert_eq!(chunks.iter().map(|chunk| chunk.data.len()).sum::<usize>(), dftest.data.len());
+        assert_eq!(dftest.chunks(0).len(), 1);
+    }
+
+    fn regions() -> DataFrame {
+        frame(&["region", "sales"], vec![
+            vec![text("north"), int(1)],
+            vec![None, int(2)],
+            vec![text("north"), int(3)],
+        ])
+    }
+
+    #[test]
+    fn partition_by_keeps_whole_rows() {
+        let regions = regions();
+        let parts = regions.partition_by(0);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts["north"].column(1), vec![Some(&CellValues::Int(1)), Some(&CellValues::Int(3))]);
+        assert_eq!(parts[NULL_PARTITION_KEY].data.len(), 1);
+        assert_eq!(parts["north"].headers, regions.headers);
+    }
+
+    #[test]
+    fn partition_by_escapes_values_that_look_like_the_null_key() {
+        let tags = frame(&["tag"], vec![vec![text("<null>")], vec![None], vec![text("\\a")]]);
+        let parts = tags.partition_by(0);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[NULL_PARTITION_KEY].column(0), vec![None]);
+        assert_eq!(parts["\\<null>"].column(0), vec![Some(&CellValues::from("<null>"))]);
+        assert_eq!(parts["\\\\a"].data.len(), 1);
+    }
+
+    #[test]
+    fn rolling_apply_skips_incomplete_windows() {
+        let weighted = regions().rolling_apply(1, 2, |window| Some(window[0]? + 2.0 * window[1]?));
+        assert_eq!(weighted, vec![None, Some(5.0), Some(8.0)]);
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimals_keep_money_exact() {
+        let mut invoice = frame(&["amount"], vec![
+            vec![Some(CellValues::Float(0.1))],
+            vec![Some(CellValues::Float(0.2))],
+            vec![text("19.99")],
+        ]);
+        assert_eq!(invoice.cast_column(0, DType::Decimal), 0);
+        assert_eq!(invoice.sum_decimal(0), Decimal::parse("20.29"));
+        assert_eq!(invoice.dtypes(), vec![Some(DType::Decimal)]);
+        let price = Decimal::parse("19.99").expect("valid decimal");
+        assert_eq!(price.checked_mul(Decimal::new(3, 0)).map(|total| total.to_string()), Some("59.97".to_string()));
+        assert_eq!(Decimal::parse("-0.050").map(|val| val.to_string()), Some("-0.050".to_string()));
+    }
+
+    #[test]
+    fn bools_are_only_inferred_when_asked() {
+        let mut survey = DataFrame::new(vec![]);
+        survey.read("data/survey.csv", ReadOptions::default().with_headers(true)).expect("failed to read file");
+        assert_eq!(survey.dtypes(), vec![Some(DType::Text), Some(DType::Int), Some(DType::Int)]);
+        let options = ReadOptions::default().with_headers(true).bool_tokens(BoolTokens::default());
+        survey.read("data/survey.csv", options).expect("failed to read file");
+        assert_eq!(survey.dtypes(), vec![Some(DType::Bool), Some(DType::Bool), Some(DType::Int)]);
+        assert_eq!(survey.column(0), vec![Some(&CellValues::Bool(true)), Some(&CellValues::Bool(false)), None]);
+    }
+
+    #[test]
+    fn csv_applies_forced_types_and_number_formats_to_the_text() {
+        let mut zips = DataFrame::new(vec![]);
+        let european = NumberFormat { decimal_separator: ',', thousands_separator: Some('.') };
+        let options = ReadOptions::default().with_headers(true).column_type(0, DType::Text).number_format(european);
+        let report = zips.read("data/zips.csv", options).expect("failed to read file");
+        assert_eq!(report, ReadReport::default());
+        assert_eq!(zips.column(0), vec![Some(&CellValues::from("00501")), Some(&CellValues::from("02134"))]);
+        assert_eq!(zips.column(1), vec![Some(&CellValues::Int(1234)), Some(&CellValues::Float(12345.5))]);
+    }
+
+    #[test]
+    fn bool_inference_respects_forced_types_and_tokens() {
+        let mut survey = DataFrame::new(vec![]);
+        let tokens = BoolTokens::new(&["yes", "1"], &["no", "0"]);
+        let options = ReadOptions::default().with_headers(true).bool_tokens(tokens).column_type(1, DType::Int);
+        survey.read("data/survey.csv", options).expect("failed to read file");
+        assert_eq!(survey.dtypes(), vec![Some(DType::Bool), Some(DType::Int), Some(DType::Int)]);
+        let tokens = BoolTokens::new(&["2"], &["1"]);
+        let options = ReadOptions::default().with_headers(true).bool_tokens(tokens).column_type(2, DType::Bool);
+        let report = survey.read("data/survey.csv", options).expect("failed to read file");
+        assert_eq!(survey.column(2), vec![Some(&CellValues::Bool(true)), Some(&CellValues::Bool(false)), None]);
+        assert_eq!(report.coercion_failures, vec![(2, DType::Bool, 1)]);
+    }
+
+    #[test]
+    fn xlsx_text_numbers_are_only_parsed_with_a_number_format() {
+        let readings = frame(&["reading"], vec![vec![text("1.5e3")], vec![text("00501")]]);
+        let xlsx_path = std::env::temp_dir().join("dataframe_text_numbers.xlsx");
+        let xlsx_path = xlsx_path.to_str().expect("temp path is not UTF-8");
+        readings.write_to_xlsx(xlsx_path, "Sheet1").expect("failed to write file");
+        let mut reread = DataFrame::new(vec![]);
+        reread.read(xlsx_path, ReadOptions::default().with_headers(true)).expect("failed to read file");
+        assert_eq!(reread.column(0), readings.column(0));
+        let plain = NumberFormat { decimal_separator: '.', thousands_separator: None };
+        reread.read(xlsx_path, ReadOptions::default().with_headers(true).number_format(plain)).expect("failed to read file");
+        assert_eq!(reread.column(0), vec![Some(&CellValues::Float(1500.0)), Some(&CellValues::Int(501))]);
+    }
+
+    #[test]
+    fn byte_order_mark_is_stripped() {
+        let mut with_bom = DataFrame::new(vec![]);
+        with_bom.read("data/bom.csv", ReadOptions::default().with_headers(true)).expect("failed to read file");
+        assert_eq!(with_bom.headers, Some(vec!["Name".to_string(), "Age".to_string()]));
+        assert!(with_bom.has_column("Name"));
+    }
+
+    fn customers() -> DataFrame {
+        frame(&["id", "name"], vec![vec![int(1), text("Ann")], vec![int(2), text("Bob")], vec![None, text("Cy")]])
+    }
+
+    fn orders() -> DataFrame {
+        frame(&["customer", "total"], vec![vec![int(1), int(10)], vec![int(1), int(20)], vec![int(3), int(5)]])
+    }
+
+    #[test]
+    fn join_pairs_matching_rows() {
+        let joined = customers().join(&orders(), 0, 0);
+        assert_eq!(joined.headers, Some(vec!["id".to_string(), "name".to_string(), "total".to_string()]));
+        assert_eq!(joined.column(2), vec![Some(&CellValues::Int(10)), Some(&CellValues::Int(20))]);
+    }
+
+    #[test]
+    fn join_suffixes_clashing_right_columns() {
+        let labels = frame(&["id", "name"], vec![vec![int(1), text("first")]]);
+        let joined = customers().join(&labels, 0, 0);
+        assert_eq!(joined.headers, Some(vec!["id".to_string(), "name".to_string(), "name_right".to_string()]));
+        assert_eq!(joined.column_index("name_right"), Some(2));
+    }
+
+    #[test]
+    fn join_left_keeps_unmatched_left_rows() {
+        let joined = customers().join_left(&orders(), 0, 0);
+        assert_eq!(joined.n_rows(), 4);
+        assert_eq!(joined.column(2), vec![Some(&CellValues::Int(10)), Some(&CellValues::Int(20)), None, None]);
+    }
+
+    #[test]
+    fn join_outer_adds_unmatched_right_rows() {
+        let joined = customers().join_outer(&orders(), 0, 0);
+        assert_eq!(joined.n_rows(), 5);
+        let last = joined.data.last().expect("joined has rows");
+        assert_eq!(last[0].value, Some(CellValues::Int(3)));
+        assert_eq!(last[1].value, None);
+        assert_eq!(last[2].value, Some(CellValues::Int(5)));
+    }
+
+    #[test]
+    fn reorder_columns_moves_named_columns_first() {
+        let mut people = frame(&["a", "b", "c"], vec![vec![int(1), int(2), int(3)]]);
+        people.reorder_columns(&["c", "a"]).expect("columns exist");
+        assert_eq!(people.headers, Some(vec!["c".to_string(), "a".to_string(), "b".to_string()]));
+        assert_eq!(people.column(2), vec![Some(&CellValues::Int(2))]);
+        assert!(matches!(people.reorder_columns(&["x"]), Err(DataFrameError::ColumnNotFound(_))));
+        assert!(matches!(people.reorder_columns(&["a", "a"]), Err(DataFrameError::DuplicateColumn(_))));
+    }
+
+    #[test]
+    fn crosstab_counts_pairs() {
+        let sales = frame(&["region", "product"], vec![
+            vec![text("north"), text("tea")],
+            vec![text("south"), text("tea")],
+            vec![text("north"), text("tea")],
+            vec![text("north"), None],
+        ]);
+        let table = sales.crosstab(0, 1);
+        assert_eq!(table.headers, Some(vec!["region".to_string(), "tea".to_string()]));
+        assert_eq!(table.column(1), vec![Some(&CellValues::Int(2)), Some(&CellValues::Int(1))]);
+    }
+
+    #[test]
+    fn nan_is_counted_like_any_other_value() {
+        let mut readings = frame(&["reading", "site"], vec![
+            vec![text("NaN"), text("a")],
+            vec![text("1.5"), text("a")],
+            vec![text("NaN"), text("b")],
+        ]);
+        assert_eq!(readings.cast_column(0, DType::Float), 0);
+        let counts = readings.value_counts(0);
+        assert_eq!(counts.len(), 2);
+        assert!(matches!(counts[0], (CellValues::Float(val), 2) if val.is_nan()));
+        assert_eq!(readings.crosstab(0, 1).n_rows(), 2);
+        assert_eq!(readings.one_hot(0).n_cols(), 3);
+    }
+
+    #[test]
+    fn out_of_range_serials_dont_become_dates() {
+        let mut ids = frame(&["id"], vec![vec![int(2_000_000_000)], vec![Some(CellValues::Float(1e12))], vec![int(45_000)]]);
+        assert_eq!(ids.cast_column(0, DType::Date), 2);
+        assert_eq!(ids.column(0)[..2], [None, None]);
+        let last = Date::from_excel_datetype(MAX_EXCEL_SERIAL).expect("serial is in range");
+        assert_eq!(last, Date { year: 9999, month: 12, day: 31 });
+        assert!(Date::from_excel_datetype(MAX_EXCEL_SERIAL + 1).is_err());
+    }
+
+    #[test]
+    fn coercion_policies_keep_rows_aligned() {
+        let amounts = frame(&["amount"], vec![vec![text("x")], vec![int(1)], vec![None]]);
+        let plain = amounts.column_as_f64(0);
+        assert_eq!(plain, vec![None, Some(1.0), None]);
+        assert_eq!(amounts.column_as_f64_with(0, CoercionPolicy::Skip).expect("skip never fails"), plain);
+        let result = amounts.column_as_f64_with(0, CoercionPolicy::Error);
+        assert!(matches!(result, Err(DataFrameError::NonNumeric { col: 0, row: 0 })));
+    }
+
+    #[test]
+    fn reads_report_cells_that_dont_fit_a_forced_type() {
+        let mut survey = DataFrame::new(vec![]);
+        let options = ReadOptions::default().with_headers(true).column_type(0, DType::Int).column_type(2, DType::Int);
+        let report = survey.read("data/survey.csv", options).expect("failed to read file");
+        assert_eq!(report.coercion_failures, vec![(0, DType::Int, 2)]);
+        assert!(survey.column(0).iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn parse_dates_counts_failures() {
+        let mut days = frame(&["day"], vec![vec![text("21.04.2024")], vec![text("soon")], vec![None]]);
+        assert_eq!(days.parse_dates(0, "DD.MM.YYYY").expect("format is valid"), (1, 1));
+        assert_eq!(days.column(0)[1], None);
+    }
+
+    #[test]
+    fn json_writes_bools_as_literals() {
+        let flags = frame(&["paid"], vec![vec![Some(CellValues::Bool(true))], vec![None]]);
+        assert_eq!(flags.to_json(None), r#"[{"paid":true},{"paid":null}]"#);
+    }
+
+    #[test]
+    fn diff_beyond_the_column_is_all_null() {
+        let series = frame(&["value"], vec![vec![int(1)], vec![int(4)], vec![int(9)]]);
+        assert_eq!(series.diff_column(0, usize::MAX), vec![None; 3]);
+        assert_eq!(series.pct_change(0, usize::MAX), vec![None; 3]);
+    }
+
+    #[test]
+    fn markdown_keeps_multiline_cells_on_one_row() {
+        let notes = frame(&["note"], vec![vec![text("x\ny")], vec![text("a\r\nb|c")]]);
+        assert_eq!(notes.to_markdown(), "| note      |\n| --------- |\n| x<br>y    |\n| a<br>b\\|c |\n");
+    }
+
+    #[test]
+    fn cut_checks_bins_and_labels() {
+        let ages = frame(&["age"], vec![vec![int(0)], vec![int(30)], vec![int(70)]]);
+        let groups = ages.cut(0, &[0.0, 18.0, 65.0], Some(&["child", "adult"])).expect("bins are valid");
+        assert_eq!(groups, vec![Some(CellValues::from("child")), Some(CellValues::from("adult")), None]);
+        assert!(matches!(ages.cut(0, &[0.0, 65.0, 18.0], None), Err(DataFrameError::UnsortedBins)));
+        assert!(matches!(ages.cut(0, &[0.0, f64::NAN], None), Err(DataFrameError::UnsortedBins)));
+        let result = ages.cut(0, &[0.0, 18.0, 65.0], Some(&["child"]));
+        assert!(matches!(result, Err(DataFrameError::LengthMismatch { expected: 2, found: 1 })));
+    }
+
+    #[test]
+    fn csv_writes_non_finite_floats_as_nulls() {
+        let float = |val: f64| Some(CellValues::Float(val));
+        let readings = frame(&["id", "reading"], vec![vec![int(1), float(f64::NAN)], vec![int(2), float(f64::INFINITY)], vec![int(3), float(1.5)]]);
+        let csv_path = std::env::temp_dir().join("dataframe_non_finite.csv");
+        let csv_path = csv_path.to_str().expect("temp path is not UTF-8");
+        readings.write_to_csv(csv_path, None).expect("failed to write file");
+        let mut reread = DataFrame::new(vec![]);
+        reread.read(csv_path, ReadOptions::default().with_headers(true)).expect("failed to read file");
+        assert_eq!(reread.column(1), vec![None, None, Some(&CellValues::Float(1.5))]);
+    }
+
+    #[test]
+    fn load_rows_allocates_once_per_row() {
+        let sheet: Vec<Vec<Data>> = (0..100_000).map(|row| (0..10).map(|col| Data::Int(row * 10 + col)).collect()).collect();
+        let rows: Vec<&[Data]> = sheet.iter().map(Vec::as_slice).collect();
+        let mut loaded = DataFrame::new(vec![]);
+        let options = ReadOptions::default().with_headers(false);
+        let allocations = allocations_during(|| {
+            loaded.load_rows(rows, "Sheet1", None, &options).expect("rows load");
+        });
+        assert_eq!(loaded.shape(), (100_000, 10));
+        // One buffer per row plus a few fixed ones; growing the rows cell by cell would take three each
+        assert!(allocations < 100_100, "{} allocations", allocations);
+    }
+
+    #[test]
+    fn rank_averages_ties() {
+        let scores = frame(&["score"], vec![vec![int(10)], vec![int(20)], vec![None], vec![int(20)], vec![int(30)]]);
+        assert_eq!(scores.rank(0, true), vec![Some(1.0), Some(2.5), None, Some(2.5), Some(4.0)]);
+        assert_eq!(scores.rank(0, false), vec![Some(4.0), Some(2.5), None, Some(2.5), Some(1.0)]);
+    }
 }